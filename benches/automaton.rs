@@ -0,0 +1,303 @@
+//! Benchmarks for building, biunifying, reducing and cloning automata. Run with
+//! `cargo bench --features std_cons,arbitrary`.
+//!
+//! Each operation is measured on types generated by proptest strategies with a fixed seed, as
+//! well as on hand-written cases which are known to be expensive.
+
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use mlsub::arbitrary::{arb_constraint, arb_polar_ty, arb_std_cons};
+use mlsub::auto::{flow, Automaton, StateId};
+use mlsub::polar::Ty;
+use mlsub::std_cons::{Constructed, Constructor};
+use mlsub::Polarity;
+use proptest::strategy::{Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+type PolarTy = Ty<Constructed<char>, char>;
+type Constraint = (PolarTy, PolarTy);
+// An automaton, along with the states to reduce or clone.
+type Input = (Automaton<Constructor>, Vec<(StateId, Polarity)>);
+
+// Positive types to build, reduce and clone.
+fn types() -> Vec<(&'static str, Vec<PolarTy>)> {
+    let cases = vec![
+        (
+            "generated",
+            sample(arb_polar_ty(Polarity::Pos, arb_std_cons), 64),
+        ),
+        ("deep_record", vec![deep_record(256)]),
+        ("fun_chain", vec![fun_chain(64, Ty::UnboundVar('a'))]),
+        ("recursive", vec![recursive(32)]),
+    ];
+    for (_, tys) in &cases {
+        for ty in tys {
+            assert!(ty.validate(Polarity::Pos).is_ok());
+        }
+    }
+    cases
+}
+
+// Constraints to biunify, and then reduce and clone the solved automaton.
+fn constraints() -> Vec<(&'static str, Vec<Constraint>)> {
+    let cases = vec![
+        ("generated", sample(arb_constraint(arb_std_cons), 64)),
+        ("deep_record", vec![(deep_record(256), deep_record(256))]),
+        (
+            "fun_chain",
+            vec![(fun_chain(64, Ty::UnboundVar('a')), fun_chain(64, bool()))],
+        ),
+        ("recursive", vec![(recursive(32), recursive(32))]),
+        ("var_chain", var_chain(250)),
+    ];
+    for (_, constraints) in &cases {
+        for (pos, neg) in constraints {
+            assert!(pos.validate(Polarity::Pos).is_ok());
+            assert!(neg.validate(Polarity::Neg).is_ok());
+        }
+    }
+    cases
+}
+
+// {x: {x: ... {x: bool, y: bool} ..., y: bool}, y: bool}
+fn deep_record(depth: usize) -> PolarTy {
+    (0..depth).fold(bool(), |ty, _| record(vec![("x", ty), ("y", bool())]))
+}
+
+// 'a -> 'a -> ... -> 'a -> last
+fn fun_chain(len: usize, last: PolarTy) -> PolarTy {
+    (0..len).fold(last, |ty, _| {
+        Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::UnboundVar('a')),
+            Box::new(ty),
+        ))
+    })
+}
+
+// rec a. {x: rec b. {x: ..., y: b, z: a}, y: a, z: a}
+fn recursive(depth: usize) -> PolarTy {
+    (0..depth).rev().fold(Ty::BoundVar(depth - 1), |ty, idx| {
+        Ty::Recursive(Box::new(record(vec![
+            ("x", ty),
+            ("y", Ty::BoundVar(0)),
+            ("z", Ty::BoundVar(idx)),
+        ])))
+    })
+}
+
+// bool ≤ α₀, α₀ ≤ α₁, ..., αₙ₋₁ ≤ αₙ
+fn var_chain(len: u32) -> Vec<Constraint> {
+    let var = |idx| Ty::UnboundVar(std::char::from_u32('a' as u32 + idx).unwrap());
+    (0..len)
+        .map(|idx| (var(idx), var(idx + 1)))
+        .chain(Some((bool(), var(0))))
+        .collect()
+}
+
+fn record(fields: Vec<(&str, PolarTy)>) -> PolarTy {
+    Ty::Constructed(Constructed::Record(
+        fields
+            .into_iter()
+            .map(|(label, ty)| (Rc::from(label), ty))
+            .collect::<BTreeMap<_, _>>(),
+    ))
+}
+
+fn bool() -> PolarTy {
+    Ty::Constructed(Constructed::Base(Rc::from("bool")))
+}
+
+// Generates values from a strategy with a fixed seed, so that each run sees the same inputs.
+fn sample<S: Strategy>(strategy: S, count: usize) -> Vec<S::Value> {
+    let mut runner = TestRunner::deterministic();
+    (0..count)
+        .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+        .collect()
+}
+
+// Builds a polar type, using the type variable in `vars` for each name, or a new one if there is
+// none yet.
+fn build_polar(
+    auto: &mut Automaton<Constructor>,
+    vars: &mut HashMap<char, flow::Pair>,
+    pol: Polarity,
+    ty: &PolarTy,
+) -> StateId {
+    let ty = bind_vars(auto, vars, ty);
+    auto.build_polar_simple(pol, &ty).unwrap()
+}
+
+fn bind_vars(
+    auto: &mut Automaton<Constructor>,
+    vars: &mut HashMap<char, flow::Pair>,
+    ty: &PolarTy,
+) -> Ty<Constructed<flow::Pair>, flow::Pair> {
+    let mut bind = |ty| bind_vars(auto, vars, ty);
+    match ty {
+        Ty::Zero => Ty::Zero,
+        Ty::Add(l, r) => {
+            let l = bind(l);
+            Ty::Add(Box::new(l), Box::new(bind(r)))
+        }
+        Ty::UnboundVar(name) => {
+            Ty::UnboundVar(*vars.entry(*name).or_insert_with(|| auto.build_var()))
+        }
+        Ty::BoundVar(idx) => Ty::BoundVar(*idx),
+        Ty::Constructed(con) => Ty::Constructed(match con {
+            Constructed::Base(name) => Constructed::Base(name.clone()),
+            Constructed::Fun(d, r) => {
+                let d = bind(d);
+                Constructed::Fun(Box::new(d), Box::new(bind(r)))
+            }
+            Constructed::Record(fields) => Constructed::Record(
+                fields
+                    .iter()
+                    .map(|(label, ty)| (label.clone(), bind(ty)))
+                    .collect(),
+            ),
+            Constructed::Variant(tags) => Constructed::Variant(
+                tags.iter()
+                    .map(|(label, ty)| (label.clone(), bind(ty)))
+                    .collect(),
+            ),
+            Constructed::Tuple(elems) => Constructed::Tuple(elems.iter().map(bind).collect()),
+            Constructed::Ref(r, w) => {
+                let r = bind(r);
+                Constructed::Ref(Box::new(r), Box::new(bind(w)))
+            }
+        }),
+        Ty::Recursive(ty) => Ty::Recursive(Box::new(bind(ty))),
+    }
+}
+
+// Builds both sides of each constraint t⁺ ≤ t⁻, with type variables shared between all of them.
+fn build_constraints(
+    auto: &mut Automaton<Constructor>,
+    constraints: &[Constraint],
+) -> Vec<(StateId, StateId)> {
+    let mut vars = HashMap::new();
+    constraints
+        .iter()
+        .map(|(pos, neg)| {
+            (
+                build_polar(auto, &mut vars, Polarity::Pos, pos),
+                build_polar(auto, &mut vars, Polarity::Neg, neg),
+            )
+        })
+        .collect()
+}
+
+fn built(tys: &[PolarTy]) -> Input {
+    let mut auto = Automaton::new();
+    let roots = tys
+        .iter()
+        .map(|ty| {
+            let id = build_polar(&mut auto, &mut HashMap::new(), Polarity::Pos, ty);
+            (id, Polarity::Pos)
+        })
+        .collect();
+    (auto, roots)
+}
+
+fn solved(constraints: &[Constraint]) -> Input {
+    let mut auto = Automaton::new();
+    let ids = build_constraints(&mut auto, constraints);
+    // Generated constraints are often unsatisfiable, so solve as much as possible.
+    let _ = auto.biunify_all_errors(ids.iter().copied());
+    let roots = ids
+        .into_iter()
+        .flat_map(|(pos, neg)| vec![(pos, Polarity::Pos), (neg, Polarity::Neg)])
+        .collect();
+    (auto, roots)
+}
+
+// Every input to reduce and clone, along with a function to create it.
+fn inputs() -> Vec<(String, Box<dyn Fn() -> Input>)> {
+    let types = types().into_iter().map(|(name, tys)| {
+        let setup: Box<dyn Fn() -> _> = Box::new(move || built(&tys));
+        (format!("built/{}", name), setup)
+    });
+    let constraints = constraints().into_iter().map(|(name, constraints)| {
+        let setup: Box<dyn Fn() -> _> = Box::new(move || solved(&constraints));
+        (format!("solved/{}", name), setup)
+    });
+    types.chain(constraints).collect()
+}
+
+fn bench_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+    for (name, tys) in types() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &tys, |b, tys| {
+            b.iter(|| built(tys))
+        });
+    }
+    group.finish();
+}
+
+fn bench_biunify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("biunify");
+    for (name, constraints) in constraints() {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            &constraints,
+            |b, constraints| {
+                b.iter_batched(
+                    || {
+                        let mut auto = Automaton::new();
+                        let ids = build_constraints(&mut auto, constraints);
+                        (auto, ids)
+                    },
+                    |(mut auto, ids)| {
+                        let _ = auto.biunify_all_errors(ids);
+                        auto
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_reduce(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reduce");
+    for (name, setup) in inputs() {
+        let (auto, roots) = setup();
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                let mut dfa = Automaton::new();
+                dfa.reduce(&auto, roots.iter().copied());
+                dfa
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_clone_states(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clone_states");
+    for (name, setup) in inputs() {
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter_batched(
+                &setup,
+                |(mut auto, roots)| {
+                    auto.clone_states(roots);
+                    auto
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_build,
+    bench_biunify,
+    bench_reduce,
+    bench_clone_states
+);
+criterion_main!(benches);
@@ -0,0 +1,698 @@
+//! Derive macros for [mlsub](https://docs.rs/mlsub).
+
+extern crate proc_macro;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, GenericArgument, Ident, Lit,
+    Member, Meta, MetaList, NestedMeta, PathArguments, Type, Visibility,
+};
+
+/// Derives `mlsub::Constructor` and `PartialOrd` for an enum of type constructors, along with
+/// its label and component types and a polar type constructor implementing `mlsub::auto::Build`
+/// and `mlsub::auto::Decompile`.
+///
+/// Each type parameter of a constructor is a field annotated with `#[covariant]` or
+/// `#[contravariant]`, and is either a `StateSet` or an `im::OrdMap` from names to `StateSet`s.
+/// Maps are annotated `#[covariant(record)]` for records, where a join has only the common
+/// fields, or `#[covariant(variant)]` for polymorphic variants, where a join has every tag.
+/// Unannotated fields are data which distinguishes constructors, such as the name of a base
+/// type, and must implement `Clone` and `Ord`.
+///
+/// ```ignore
+/// #[derive(Clone, Debug, PartialEq, Constructor)]
+/// #[constructor(label = "Label", component = "Component", build = "Constructed")]
+/// enum Cons {
+///     Bool,
+///     Fun {
+///         #[contravariant]
+///         domain: StateSet,
+///         #[covariant]
+///         range: StateSet,
+///     },
+///     Record(#[covariant(record)] OrdMap<Rc<str>, StateSet>),
+///     Variant(#[covariant(variant)] OrdMap<Rc<str>, StateSet>),
+/// }
+/// ```
+///
+/// The generated label type has a variant for each type parameter, named after its constructor
+/// and field, such as `Label::FunDomain`, or just the constructor for a constructor with a single
+/// unnamed field, such as `Label::Record(Rc<str>)`. The names of the generated types default to
+/// the name of the enum followed by `Label`, `Component` and `Build`.
+#[proc_macro_derive(Constructor, attributes(constructor, covariant, contravariant))]
+pub fn derive_constructor(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+struct Names {
+    label: Ident,
+    component: Ident,
+    build: Ident,
+}
+
+struct Variant {
+    ident: Ident,
+    fields: Fields,
+    params: Vec<Field>,
+}
+
+struct Field {
+    member: Member,
+    ty: Type,
+    kind: Kind,
+}
+
+enum Kind {
+    Data,
+    Param {
+        pol: Pol,
+        label: Ident,
+    },
+    // A map of type parameters, with width subtyping for records and the dual for variants.
+    Map {
+        pol: Pol,
+        label: Ident,
+        key: Type,
+        variant: bool,
+    },
+}
+
+#[derive(Copy, Clone)]
+enum Pol {
+    Pos,
+    Neg,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "generic constructors are not supported",
+        ));
+    }
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`Constructor` can only be derived for enums",
+            ))
+        }
+    };
+
+    let names = parse_names(&input.ident, &input.attrs)?;
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| {
+            let params = variant
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(idx, field)| parse_field(variant, idx, field))
+                .collect::<Result<_, _>>()?;
+            Ok(Variant {
+                ident: variant.ident.clone(),
+                fields: variant.fields.clone(),
+                params,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let label = expand_label(&input.vis, &names, &variants);
+    let component = expand_component(&input.vis, &names, &variants);
+    let constructor = expand_constructor(&input.ident, &names, &variants);
+    let partial_ord = expand_partial_ord(&input.ident, &variants);
+    let build = expand_build(&input.vis, &input.ident, &names, &variants);
+
+    Ok(quote! {
+        #label
+        #component
+        #constructor
+        #partial_ord
+        #build
+    })
+}
+
+fn parse_names(ident: &Ident, attrs: &[Attribute]) -> Result<Names, Error> {
+    let mut names = Names {
+        label: format_ident!("{}Label", ident),
+        component: format_ident!("{}Component", ident),
+        build: format_ident!("{}Build", ident),
+    };
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("constructor"))
+    {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(Error::new(meta.span(), "expected `constructor(...)`")),
+        };
+        for nested in list.nested {
+            let (path, lit) = match &nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) => (&nv.path, &nv.lit),
+                _ => return Err(Error::new(nested.span(), "expected `name = \"...\"`")),
+            };
+            let value = match lit {
+                Lit::Str(lit) => lit.parse::<Ident>()?,
+                _ => return Err(Error::new(lit.span(), "expected a string")),
+            };
+            if path.is_ident("label") {
+                names.label = value;
+            } else if path.is_ident("component") {
+                names.component = value;
+            } else if path.is_ident("build") {
+                names.build = value;
+            } else {
+                return Err(Error::new(path.span(), "unknown `constructor` attribute"));
+            }
+        }
+    }
+    Ok(names)
+}
+
+fn parse_field(variant: &syn::Variant, idx: usize, field: &syn::Field) -> Result<Field, Error> {
+    let member = match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(idx.into()),
+    };
+    let label = match &field.ident {
+        Some(ident) => format_ident!("{}{}", variant.ident, camel_case(ident)),
+        None if variant.fields.len() == 1 => variant.ident.clone(),
+        None => format_ident!("{}{}", variant.ident, idx),
+    };
+
+    let mut kind = Kind::Data;
+    for attr in &field.attrs {
+        let pol = if attr.path.is_ident("covariant") {
+            Pol::Pos
+        } else if attr.path.is_ident("contravariant") {
+            Pol::Neg
+        } else {
+            continue;
+        };
+        if !matches!(kind, Kind::Data) {
+            return Err(Error::new(attr.span(), "duplicate variance attribute"));
+        }
+        kind = match attr.parse_meta()? {
+            Meta::Path(_) => Kind::Param {
+                pol,
+                label: label.clone(),
+            },
+            Meta::List(list) if map_kind(&list).is_some() => Kind::Map {
+                pol,
+                label: label.clone(),
+                key: map_key(&field.ty)?,
+                variant: map_kind(&list) == Some("variant"),
+            },
+            meta => {
+                return Err(Error::new(
+                    meta.span(),
+                    "expected `covariant`, `covariant(record)` or `covariant(variant)`",
+                ))
+            }
+        };
+    }
+
+    Ok(Field {
+        member,
+        ty: field.ty.clone(),
+        kind,
+    })
+}
+
+fn map_kind(list: &MetaList) -> Option<&'static str> {
+    match list.nested.iter().collect::<Vec<_>>().as_slice() {
+        [NestedMeta::Meta(Meta::Path(path))] => ["record", "variant"]
+            .iter()
+            .copied()
+            .find(|&kind| path.is_ident(kind)),
+        _ => None,
+    }
+}
+
+// Finds the key type of a map type such as `OrdMap<Rc<str>, StateSet>`.
+fn map_key(ty: &Type) -> Result<Type, Error> {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(GenericArgument::Type(key)) = args.args.first() {
+                    return Ok(key.clone());
+                }
+            }
+        }
+    }
+    Err(Error::new(
+        ty.span(),
+        "expected a map type such as `OrdMap<K, StateSet>`",
+    ))
+}
+
+fn camel_case(ident: &Ident) -> String {
+    ident
+        .to_string()
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+// Bindings for the fields of a variant, such as `Cons::Fun { domain: l0, range: l1 }`. Braced
+// syntax works for unit and tuple variants too, so this can be used as a pattern or expression.
+fn pattern(path: TokenStream, variant: &Variant, prefix: &str) -> TokenStream {
+    let members = variant.params.iter().map(|field| &field.member);
+    let bindings = binding_idents(variant, prefix);
+    quote! { #path { #(#members: #bindings),* } }
+}
+
+fn binding_idents(variant: &Variant, prefix: &str) -> Vec<Ident> {
+    (0..variant.params.len())
+        .map(|idx| Ident::new(&format!("{}{}", prefix, idx), Span::call_site()))
+        .collect()
+}
+
+fn expand_label(vis: &Visibility, names: &Names, variants: &[Variant]) -> TokenStream {
+    let label = &names.label;
+    let mut decls = Vec::new();
+    let mut arms = Vec::new();
+    for field in variants.iter().flat_map(|variant| &variant.params) {
+        match &field.kind {
+            Kind::Data => (),
+            Kind::Param { pol, label: ident } => {
+                decls.push(quote! { #ident });
+                arms.push(quote! { #label::#ident => #pol });
+            }
+            Kind::Map {
+                pol,
+                label: ident,
+                key,
+                ..
+            } => {
+                decls.push(quote! { #ident(#key) });
+                arms.push(quote! { #label::#ident(_) => #pol });
+            }
+        }
+    }
+
+    quote! {
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        #vis enum #label {
+            #(#decls,)*
+        }
+
+        impl ::mlsub::Label for #label {
+            fn polarity(&self) -> ::mlsub::Polarity {
+                match *self {
+                    #(#arms,)*
+                }
+            }
+        }
+    }
+}
+
+fn expand_component(vis: &Visibility, names: &Names, variants: &[Variant]) -> TokenStream {
+    let component = &names.component;
+    let decls = variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let tys = data_fields(variant).map(|(_, field)| &field.ty);
+        if data_fields(variant).next().is_some() {
+            quote! { #name(#(#tys),*) }
+        } else {
+            quote! { #name }
+        }
+    });
+    quote! {
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        #vis enum #component {
+            #(#decls,)*
+        }
+    }
+}
+
+fn data_fields(variant: &Variant) -> impl Iterator<Item = (Ident, &Field)> {
+    binding_idents(variant, "l")
+        .into_iter()
+        .zip(&variant.params)
+        .filter(|(_, field)| matches!(field.kind, Kind::Data))
+}
+
+fn expand_constructor(ident: &Ident, names: &Names, variants: &[Variant]) -> TokenStream {
+    let label = &names.label;
+    let component = &names.component;
+
+    let component_arms = variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let pat = pattern(quote!(#ident::#name), variant, "l");
+        let data = data_fields(variant).enumerate().map(|(idx, (binding, _))| {
+            let member = Member::Unnamed(idx.into());
+            quote! { #member: ::std::clone::Clone::clone(#binding) }
+        });
+        quote! { #pat => #component::#name { #(#data),* } }
+    });
+
+    let join_arms = variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let lhs = pattern(quote!(#ident::#name), variant, "l");
+        let rhs = pattern(quote!(#ident::#name), variant, "r");
+        let stmts = binding_idents(variant, "l")
+            .into_iter()
+            .zip(binding_idents(variant, "r"))
+            .zip(&variant.params)
+            .map(|((l, r), field)| match field.kind {
+                Kind::Data => quote! {},
+                Kind::Param { .. } => quote! { #l.union(#r); },
+                // A join of records has only their common fields, and a meet has all of them.
+                // Variants are the other way around.
+                Kind::Map { variant, .. } => {
+                    let pol = if variant { quote!(-pol) } else { quote!(pol) };
+                    quote! {
+                        let union = |mut l: ::mlsub::auto::StateSet, r| {
+                            l.union(&r);
+                            l
+                        };
+                        *#l = match #pol {
+                            ::mlsub::Polarity::Pos => {
+                                #l.clone().intersection_with(#r.clone(), union)
+                            }
+                            ::mlsub::Polarity::Neg => #l.clone().union_with(#r.clone(), union),
+                        };
+                    }
+                }
+            });
+        quote! { (#lhs, #rhs) => { #(#stmts)* } }
+    });
+
+    let visit_arms = variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let lhs = pattern(quote!(#ident::#name), variant, "l");
+        let rhs = pattern(quote!(#ident::#name), variant, "r");
+        let stmts = binding_idents(variant, "l")
+            .into_iter()
+            .zip(binding_idents(variant, "r"))
+            .zip(&variant.params)
+            .map(|((l, r), field)| match &field.kind {
+                Kind::Data => quote! {},
+                Kind::Param { label: ident, .. } => quote! { visit(#label::#ident, #l, #r)?; },
+                Kind::Map { label: ident, .. } => quote! {
+                    for (key, l) in #l.iter() {
+                        if let Some(r) = #r.get(key) {
+                            visit(#label::#ident(::std::clone::Clone::clone(key)), l, r)?;
+                        }
+                    }
+                },
+            });
+        quote! { (#lhs, #rhs) => { #(#stmts)* } }
+    });
+
+    let map_arms = variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let pat = pattern(quote!(#ident::#name), variant, "l");
+        let members = variant.params.iter().map(|field| &field.member);
+        let exprs = binding_idents(variant, "l")
+            .into_iter()
+            .zip(&variant.params)
+            .map(|(l, field)| match &field.kind {
+                Kind::Data => quote! { #l },
+                Kind::Param { label: ident, .. } => quote! { mapper(#label::#ident, #l) },
+                Kind::Map { label: ident, .. } => quote! {
+                    #l.into_iter()
+                        .map(|(key, set)| {
+                            let label = #label::#ident(::std::clone::Clone::clone(&key));
+                            (key, mapper(label, set))
+                        })
+                        .collect()
+                },
+            });
+        quote! { #pat => #ident::#name { #(#members: #exprs),* } }
+    });
+
+    quote! {
+        impl ::mlsub::Constructor for #ident {
+            type Label = #label;
+            type Component = #component;
+
+            #[allow(unused_variables)]
+            fn component(&self) -> Self::Component {
+                match self {
+                    #(#component_arms,)*
+                }
+            }
+
+            #[allow(unused_variables, unreachable_patterns)]
+            fn join(&mut self, other: &Self, pol: ::mlsub::Polarity) {
+                match (self, other) {
+                    #(#join_arms)*
+                    _ => unreachable!(),
+                }
+            }
+
+            #[allow(unused_variables, unreachable_patterns)]
+            fn visit_params_intersection<F, E>(
+                &self,
+                other: &Self,
+                mut visit: F,
+            ) -> ::std::result::Result<(), E>
+            where
+                F: FnMut(
+                    Self::Label,
+                    &::mlsub::auto::StateSet,
+                    &::mlsub::auto::StateSet,
+                ) -> ::std::result::Result<(), E>,
+            {
+                match (self, other) {
+                    #(#visit_arms)*
+                    _ => (),
+                }
+                Ok(())
+            }
+
+            #[allow(unused_mut)]
+            fn map<F>(self, mut mapper: F) -> Self
+            where
+                F: FnMut(Self::Label, ::mlsub::auto::StateSet) -> ::mlsub::auto::StateSet,
+            {
+                match self {
+                    #(#map_arms,)*
+                }
+            }
+        }
+    }
+}
+
+fn expand_partial_ord(ident: &Ident, variants: &[Variant]) -> TokenStream {
+    let arms = variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let lhs = pattern(quote!(#ident::#name), variant, "l");
+        let rhs = pattern(quote!(#ident::#name), variant, "r");
+        let stmts = binding_idents(variant, "l")
+            .into_iter()
+            .zip(binding_idents(variant, "r"))
+            .zip(&variant.params)
+            .map(|((l, r), field)| match field.kind {
+                Kind::Data => quote! {
+                    if #l != #r {
+                        return None;
+                    }
+                },
+                Kind::Param { .. } => quote! {},
+                // A record with more fields is a subtype of one with fewer, and a variant with
+                // fewer tags is a subtype of one with more.
+                Kind::Map { variant, .. } => {
+                    let (sub, sup) = if variant { (&r, &l) } else { (&l, &r) };
+                    quote! {
+                        let field = match (
+                            #sup.keys().all(|key| #sub.contains_key(key)),
+                            #sub.keys().all(|key| #sup.contains_key(key)),
+                        ) {
+                            (true, true) => ::std::cmp::Ordering::Equal,
+                            (true, false) => ::std::cmp::Ordering::Less,
+                            (false, true) => ::std::cmp::Ordering::Greater,
+                            (false, false) => return None,
+                        };
+                        if ord == ::std::cmp::Ordering::Equal {
+                            ord = field;
+                        } else if field != ::std::cmp::Ordering::Equal && field != ord {
+                            return None;
+                        }
+                    }
+                }
+            });
+        quote! {
+            (#lhs, #rhs) => {
+                let mut ord = ::std::cmp::Ordering::Equal;
+                #(#stmts)*
+                Some(ord)
+            }
+        }
+    });
+
+    quote! {
+        impl ::std::cmp::PartialOrd for #ident {
+            #[allow(unused_mut, unused_variables, unreachable_patterns)]
+            fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+                match (self, other) {
+                    #(#arms)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+fn expand_build(
+    vis: &Visibility,
+    ident: &Ident,
+    names: &Names,
+    variants: &[Variant],
+) -> TokenStream {
+    let label = &names.label;
+    let build = &names.build;
+    let ty = quote! { ::mlsub::polar::Ty<#build<V>, V> };
+
+    let decls = variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let tys = variant.params.iter().map(|field| match &field.kind {
+            Kind::Data => {
+                let ty = &field.ty;
+                quote! { #ty }
+            }
+            Kind::Param { .. } => quote! { Box<#ty> },
+            Kind::Map { key, .. } => quote! { ::std::collections::BTreeMap<#key, #ty> },
+        });
+        match &variant.fields {
+            Fields::Named(_) => {
+                let members = variant.params.iter().map(|field| &field.member);
+                quote! { #name { #(#members: #tys),* } }
+            }
+            Fields::Unnamed(_) => quote! { #name(#(#tys),*) },
+            Fields::Unit => quote! { #name },
+        }
+    });
+
+    let build_arms = variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let pat = pattern(quote!(#build::#name), variant, "l");
+        let members = variant.params.iter().map(|field| &field.member);
+        let exprs = binding_idents(variant, "l")
+            .into_iter()
+            .zip(&variant.params)
+            .map(|(l, field)| match &field.kind {
+                Kind::Data => quote! { ::std::clone::Clone::clone(#l) },
+                Kind::Param { label: ident, .. } => quote! { mapper(#label::#ident, #l) },
+                Kind::Map { label: ident, .. } => quote! {
+                    #l.iter()
+                        .map(|(key, ty)| {
+                            let label = #label::#ident(::std::clone::Clone::clone(key));
+                            (::std::clone::Clone::clone(key), mapper(label, ty))
+                        })
+                        .collect()
+                },
+            });
+        quote! { #pat => #ident::#name { #(#members: #exprs),* } }
+    });
+
+    let visit_arms = variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let pat = pattern(quote!(#build::#name), variant, "l");
+        let stmts = binding_idents(variant, "l")
+            .into_iter()
+            .zip(&variant.params)
+            .map(|(l, field)| match &field.kind {
+                Kind::Data => quote! { let _ = #l; },
+                Kind::Param { label: ident, .. } => quote! { visit(#label::#ident, #l); },
+                Kind::Map { label: ident, .. } => quote! {
+                    for (key, ty) in #l {
+                        visit(#label::#ident(::std::clone::Clone::clone(key)), ty);
+                    }
+                },
+            });
+        quote! { #pat => { #(#stmts)* } }
+    });
+
+    let decompile_arms = variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let pat = pattern(quote!(#ident::#name), variant, "l");
+        let members = variant.params.iter().map(|field| &field.member);
+        let exprs = binding_idents(variant, "l")
+            .into_iter()
+            .zip(&variant.params)
+            .map(|(l, field)| match &field.kind {
+                Kind::Data => quote! { ::std::clone::Clone::clone(#l) },
+                Kind::Param { label: ident, .. } => {
+                    quote! { Box::new(mapper(#label::#ident, #l)) }
+                }
+                Kind::Map { label: ident, .. } => quote! {
+                    #l.iter()
+                        .map(|(key, set)| {
+                            let label = #label::#ident(::std::clone::Clone::clone(key));
+                            (::std::clone::Clone::clone(key), mapper(label, set))
+                        })
+                        .collect()
+                },
+            });
+        quote! { #pat => #build::#name { #(#members: #exprs),* } }
+    });
+
+    quote! {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        #vis enum #build<V> {
+            #(#decls,)*
+        }
+
+        impl<V> ::mlsub::auto::Build<#ident, V> for #build<V> {
+            #[allow(unused_mut)]
+            fn map<'a, F>(&'a self, mut mapper: F) -> #ident
+            where
+                V: 'a,
+                F: FnMut(#label, &'a ::mlsub::polar::Ty<Self, V>) -> ::mlsub::auto::StateSet,
+            {
+                match self {
+                    #(#build_arms,)*
+                }
+            }
+
+            #[allow(unused_mut)]
+            fn visit_params<'a, F>(&'a self, mut visit: F)
+            where
+                V: 'a,
+                F: FnMut(#label, &'a ::mlsub::polar::Ty<Self, V>),
+            {
+                match self {
+                    #(#visit_arms,)*
+                }
+            }
+        }
+
+        impl<V> ::mlsub::auto::Decompile<#ident, V> for #build<V> {
+            #[allow(unused_mut)]
+            fn decompile<F>(con: &#ident, mut mapper: F) -> Self
+            where
+                F: FnMut(#label, &::mlsub::auto::StateSet) -> ::mlsub::polar::Ty<Self, V>,
+            {
+                match con {
+                    #(#decompile_arms,)*
+                }
+            }
+        }
+    }
+}
+
+impl quote::ToTokens for Pol {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            Pol::Pos => quote! { ::mlsub::Polarity::Pos },
+            Pol::Neg => quote! { ::mlsub::Polarity::Neg },
+        })
+    }
+}
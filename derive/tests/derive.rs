@@ -0,0 +1,157 @@
+use std::iter::once;
+use std::rc::Rc;
+
+use im::OrdMap;
+use mlsub::auto::{flow, Automaton, StateSet};
+use mlsub::polar::Ty;
+use mlsub::{Label as _, Polarity};
+use mlsub_derive::Constructor;
+
+#[derive(Clone, Debug, PartialEq, Constructor)]
+#[constructor(label = "Label", component = "Component", build = "Constructed")]
+enum Cons {
+    Base(&'static str),
+    Fun {
+        #[contravariant]
+        domain: StateSet,
+        #[covariant]
+        range: StateSet,
+    },
+    Record(#[covariant(record)] OrdMap<Rc<str>, StateSet>),
+    Variant(#[covariant(variant)] OrdMap<Rc<str>, StateSet>),
+    Ref(#[contravariant] StateSet, #[covariant] StateSet),
+}
+
+type Polar = Ty<Constructed<flow::Pair>, flow::Pair>;
+
+fn base(name: &'static str) -> Polar {
+    Ty::Constructed(Constructed::Base(name))
+}
+
+fn fun(domain: Polar, range: Polar) -> Polar {
+    Ty::Constructed(Constructed::Fun {
+        domain: Box::new(domain),
+        range: Box::new(range),
+    })
+}
+
+fn record(fields: &[(&str, Polar)]) -> Polar {
+    Ty::Constructed(Constructed::Record(
+        fields
+            .iter()
+            .map(|(label, ty)| (Rc::from(*label), ty.clone()))
+            .collect(),
+    ))
+}
+
+fn variant(tags: &[(&str, Polar)]) -> Polar {
+    Ty::Constructed(Constructed::Variant(
+        tags.iter()
+            .map(|(tag, ty)| (Rc::from(*tag), ty.clone()))
+            .collect(),
+    ))
+}
+
+fn biunify(lhs: &Polar, rhs: &Polar) -> bool {
+    let mut auto = Automaton::new();
+    let lhs = auto.build_polar_simple(Polarity::Pos, lhs).unwrap();
+    let rhs = auto.build_polar_simple(Polarity::Neg, rhs).unwrap();
+    auto.biunify(lhs, rhs).is_ok()
+}
+
+#[test]
+fn label_polarity() {
+    assert_eq!(Label::FunDomain.polarity(), Polarity::Neg);
+    assert_eq!(Label::FunRange.polarity(), Polarity::Pos);
+    assert_eq!(Label::Record(Rc::from("x")).polarity(), Polarity::Pos);
+    assert_eq!(Label::Variant(Rc::from("A")).polarity(), Polarity::Pos);
+    assert_eq!(Label::Ref0.polarity(), Polarity::Neg);
+    assert_eq!(Label::Ref1.polarity(), Polarity::Pos);
+}
+
+#[test]
+fn biunify_base() {
+    assert!(biunify(&base("int"), &base("int")));
+    assert!(!biunify(&base("int"), &base("bool")));
+    assert!(!biunify(&base("int"), &fun(base("int"), base("int"))));
+}
+
+#[test]
+fn biunify_fun() {
+    assert!(biunify(
+        &fun(base("int"), base("bool")),
+        &fun(base("int"), base("bool"))
+    ));
+    assert!(!biunify(
+        &fun(base("int"), base("bool")),
+        &fun(base("bool"), base("bool"))
+    ));
+    assert!(!biunify(
+        &fun(base("int"), base("bool")),
+        &fun(base("int"), base("int"))
+    ));
+}
+
+#[test]
+fn biunify_record() {
+    let xy = record(&[("x", base("int")), ("y", base("bool"))]);
+    let x = record(&[("x", base("int"))]);
+    let y = record(&[("y", base("int"))]);
+
+    assert!(biunify(&xy, &x));
+    assert!(!biunify(&x, &xy));
+    assert!(!biunify(&xy, &y));
+    assert!(biunify(
+        &Ty::Add(Box::new(xy.clone()), Box::new(x.clone())),
+        &x
+    ));
+    assert!(!biunify(&x, &Ty::Add(Box::new(x.clone()), Box::new(xy))));
+}
+
+#[test]
+fn biunify_variant() {
+    let ab = variant(&[("A", base("int")), ("B", base("bool"))]);
+    let a = variant(&[("A", base("int"))]);
+    let b = variant(&[("B", base("bool"))]);
+
+    assert!(biunify(&a, &ab));
+    assert!(!biunify(&ab, &a));
+    assert!(!biunify(&a, &b));
+    assert!(biunify(
+        &Ty::Add(Box::new(a.clone()), Box::new(b.clone())),
+        &ab
+    ));
+    assert!(!biunify(&Ty::Add(Box::new(a.clone()), Box::new(b)), &a));
+    assert!(!biunify(&a, &Ty::Add(Box::new(ab), Box::new(variant(&[])))));
+}
+
+#[test]
+fn decompile() {
+    let mut nfa = Automaton::new();
+    let var = nfa.build_var();
+    let ty = Ty::Add(
+        Box::new(record(&[("x", base("int")), ("y", base("bool"))])),
+        Box::new(record(&[("x", fun(base("int"), Ty::UnboundVar(var)))])),
+    );
+    let id = nfa.build_polar_simple(Polarity::Pos, &ty).unwrap();
+    let mut dfa = Automaton::new();
+    let id = dfa.reduce(&nfa, once((id, Polarity::Pos))).next().unwrap();
+
+    let ty: Ty<Constructed<usize>, usize> = dfa.to_polar_ty(id, Polarity::Pos);
+    assert_eq!(
+        ty,
+        Ty::Constructed(Constructed::Record(
+            once((
+                Rc::from("x"),
+                Ty::Add(
+                    Box::new(Ty::Constructed(Constructed::Base("int"))),
+                    Box::new(Ty::Constructed(Constructed::Fun {
+                        domain: Box::new(Ty::Constructed(Constructed::Base("int"))),
+                        range: Box::new(Ty::Zero),
+                    })),
+                ),
+            ))
+            .collect()
+        ))
+    );
+}
@@ -0,0 +1,37 @@
+//! An interactive type checker for a small ML-like language.
+//!
+//! Each line is parsed as an expression, and its principal type is inferred and printed. See
+//! `:help` for the supported commands.
+
+mod parse;
+mod repl;
+#[cfg(test)]
+mod tests;
+
+use std::io::{self, BufRead, Write};
+
+use crate::repl::{Output, Repl, HELP};
+
+fn main() -> io::Result<()> {
+    let mut repl = Repl::new();
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    writeln!(stdout, "{}\n", HELP)?;
+    loop {
+        write!(stdout, "> ")?;
+        stdout.flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        match repl.eval(&line) {
+            Ok(Output::Print(output)) if output.is_empty() => (),
+            Ok(Output::Print(output)) => writeln!(stdout, "{}", output)?,
+            Ok(Output::Quit) => return Ok(()),
+            Err(err) => writeln!(stdout, "error: {}", err)?,
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use mlsub::infer::{Expr, Lit};
+use mlsub::polar::ParseError;
+
+/// A line of input which is not a command.
+#[derive(Debug, PartialEq)]
+pub enum Line {
+    /// A top-level binding `let x = e`.
+    Define(Rc<str>, Expr),
+    Expr(Expr),
+}
+
+const KEYWORDS: &[&str] = &["fun", "let", "in", "if", "then", "else", "true", "false"];
+
+struct Parser<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+pub fn parse_line(input: &str) -> Result<Line, ParseError> {
+    let mut parser = Parser { input, offset: 0 };
+    let line = if parser.eat("let") {
+        let (name, bound) = parser.parse_binding()?;
+        if parser.eat("in") {
+            let body = parser.parse_expr()?;
+            Line::Expr(Expr::Let(name, Box::new(bound), Box::new(body)))
+        } else {
+            Line::Define(name, bound)
+        }
+    } else {
+        Line::Expr(parser.parse_expr()?)
+    };
+    parser.expect_end()?;
+    Ok(line)
+}
+
+pub fn parse_expr(input: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser { input, offset: 0 };
+    let expr = parser.parse_expr()?;
+    parser.expect_end()?;
+    Ok(expr)
+}
+
+impl<'a> Parser<'a> {
+    fn parse_expr(&mut self) -> Result<Expr, ParseError> {
+        if self.eat("fun") {
+            let param = self.expect_ident()?;
+            self.expect("->")?;
+            let body = self.parse_expr()?;
+            Ok(Expr::Lambda(param, Box::new(body)))
+        } else if self.eat("let") {
+            let (name, bound) = self.parse_binding()?;
+            self.expect("in")?;
+            let body = self.parse_expr()?;
+            Ok(Expr::Let(name, Box::new(bound), Box::new(body)))
+        } else if self.eat("if") {
+            let cond = self.parse_expr()?;
+            self.expect("then")?;
+            let then = self.parse_expr()?;
+            self.expect("else")?;
+            let els = self.parse_expr()?;
+            Ok(Expr::If(Box::new(cond), Box::new(then), Box::new(els)))
+        } else {
+            self.parse_app()
+        }
+    }
+
+    fn parse_binding(&mut self) -> Result<(Rc<str>, Expr), ParseError> {
+        let name = self.expect_ident()?;
+        self.expect("=")?;
+        let bound = self.parse_expr()?;
+        Ok((name, bound))
+    }
+
+    fn parse_app(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = match self.parse_proj()? {
+            Some(expr) => expr,
+            None => return Err(self.error("expected expression")),
+        };
+        while let Some(arg) = self.parse_proj()? {
+            expr = Expr::App(Box::new(expr), Box::new(arg));
+        }
+        Ok(expr)
+    }
+
+    fn parse_proj(&mut self) -> Result<Option<Expr>, ParseError> {
+        let mut expr = match self.parse_atom()? {
+            Some(expr) => expr,
+            None => return Ok(None),
+        };
+        while self.eat(".") {
+            let label = self.expect_ident()?;
+            expr = Expr::Proj(Box::new(expr), label);
+        }
+        Ok(Some(expr))
+    }
+
+    fn parse_atom(&mut self) -> Result<Option<Expr>, ParseError> {
+        if self.eat("(") {
+            let expr = self.parse_expr()?;
+            self.expect(")")?;
+            Ok(Some(expr))
+        } else if self.eat("{") {
+            let mut fields = BTreeMap::new();
+            if !self.eat("}") {
+                loop {
+                    let (label, expr) = self.parse_binding()?;
+                    fields.insert(label, expr);
+                    if self.eat("}") {
+                        break;
+                    }
+                    self.expect(",")?;
+                }
+            }
+            Ok(Some(Expr::Record(fields)))
+        } else if self.eat("true") {
+            Ok(Some(Expr::Lit(Lit::Bool(true))))
+        } else if self.eat("false") {
+            Ok(Some(Expr::Lit(Lit::Bool(false))))
+        } else if let Some(n) = self.int()? {
+            Ok(Some(Expr::Lit(Lit::Int(n))))
+        } else if let Some(name) = self.ident() {
+            Ok(Some(Expr::Var(name)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.offset..];
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    fn rest(&mut self) -> &'a str {
+        self.skip_whitespace();
+        &self.input[self.offset..]
+    }
+
+    // Gets the length of the identifier or keyword at the start of the input.
+    fn word_len(&mut self) -> usize {
+        let rest = self.rest();
+        match rest.chars().next() {
+            Some(ch) if ch.is_alphabetic() || ch == '_' => rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '\''))
+                .unwrap_or(rest.len()),
+            _ => 0,
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let rest = self.rest();
+        let is_word = token.starts_with(char::is_alphabetic);
+        if (is_word && &rest[..self.word_len()] == token) || (!is_word && rest.starts_with(token)) {
+            self.offset += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), ParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", token)))
+        }
+    }
+
+    fn ident(&mut self) -> Option<Rc<str>> {
+        let len = self.word_len();
+        let word = &self.rest()[..len];
+        if len == 0 || KEYWORDS.contains(&word) {
+            None
+        } else {
+            self.offset += len;
+            Some(Rc::from(word))
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<Rc<str>, ParseError> {
+        match self.ident() {
+            Some(name) => Ok(name),
+            None => Err(self.error("expected identifier")),
+        }
+    }
+
+    fn int(&mut self) -> Result<Option<i64>, ParseError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Ok(None);
+        }
+        match rest[..len].parse() {
+            Ok(n) => {
+                self.offset += len;
+                Ok(Some(n))
+            }
+            Err(_) => Err(self.error("integer literal is too large")),
+        }
+    }
+
+    fn expect_end(&mut self) -> Result<(), ParseError> {
+        if self.rest().is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("expected end of input"))
+        }
+    }
+
+    fn error(&mut self, message: &str) -> ParseError {
+        self.skip_whitespace();
+        let len = self.input[self.offset..]
+            .chars()
+            .next()
+            .map_or(0, char::len_utf8);
+        ParseError {
+            span: self.offset..(self.offset + len),
+            message: message.to_owned(),
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+use std::iter::once;
+use std::rc::Rc;
+
+use mlsub::auto::{flow, Automaton, StateId, StateSet};
+use mlsub::infer::{Expr, Infer};
+use mlsub::polar::{ParseError, Ty, ValidateError};
+use mlsub::std_cons::{Constructed, Constructor};
+use mlsub::{Constructor as _, Label as _, Polarity};
+
+use crate::parse::{parse_expr, parse_line, Line};
+
+pub const HELP: &str = "\
+Enter an expression to print its type, or `let x = e` to bind a variable for later lines.
+
+Commands:
+  :check e : T    check that e has type T
+  :auto e         print the simplified automaton for the type of e
+  :help           print this message
+  :quit           exit";
+
+/// The state of a REPL session.
+pub struct Repl {
+    infer: Infer,
+}
+
+/// The result of evaluating a line of input.
+pub enum Output {
+    Print(String),
+    Quit,
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Repl {
+            infer: Infer::new(),
+        }
+    }
+
+    /// Evaluates a line of input. Errors are returned as a message to print.
+    pub fn eval(&mut self, line: &str) -> Result<Output, String> {
+        let line = line.trim();
+        if let Some(command) = line.strip_prefix(':') {
+            let (name, args) = match command.find(char::is_whitespace) {
+                Some(idx) => (&command[..idx], &command[idx..]),
+                None => (command, ""),
+            };
+            // The offset of `args` in the line, for the positions of parse errors.
+            let offset = line.len() - args.len();
+            match name {
+                "check" => self.check(args, offset).map(Output::Print),
+                "auto" => self.auto(args, offset).map(Output::Print),
+                "help" => Ok(Output::Print(HELP.to_owned())),
+                "quit" | "q" => Ok(Output::Quit),
+                _ => Err(format!("unknown command `:{}`, try `:help`", name)),
+            }
+        } else if line.is_empty() {
+            Ok(Output::Print(String::new()))
+        } else {
+            match parse_line(line).map_err(|err| parse_error(err, 0))? {
+                Line::Define(name, expr) => {
+                    let scheme = self
+                        .infer
+                        .define(name.clone(), &expr)
+                        .map_err(|err| err.to_string())?;
+                    let ty = self.infer.to_polar_ty(scheme.ty);
+                    Ok(Output::Print(format!(
+                        "{} : {}",
+                        name,
+                        ty.display(Polarity::Pos)
+                    )))
+                }
+                Line::Expr(expr) => {
+                    let (auto, ty) = self.infer(&expr)?;
+                    let ty: Ty<Constructed<usize>, usize> = auto.to_polar_ty(ty, Polarity::Pos);
+                    Ok(Output::Print(ty.display(Polarity::Pos).to_string()))
+                }
+            }
+        }
+    }
+
+    // Infers the type of an expression without adding it to the session, returning a simplified
+    // copy of it in a separate automaton.
+    fn infer(&mut self, expr: &Expr) -> Result<(Automaton<Constructor>, StateId), String> {
+        self.infer
+            .infer_scratch(expr, |auto, scheme| simplify(auto, scheme.ty))
+            .map_err(|err| err.to_string())
+    }
+
+    // Checks whether the type of an expression subsumes a given type, that is, whether the
+    // expression can be used wherever a value of that type is expected.
+    fn check(&mut self, args: &str, offset: usize) -> Result<String, String> {
+        let colon = match args.find(':') {
+            Some(colon) => colon,
+            None => return Err("expected `:check e : T`".to_owned()),
+        };
+        let expr = parse_expr(&args[..colon]).map_err(|err| parse_error(err, offset))?;
+        let expected: Ty<Constructed<String>, String> =
+            Ty::parse(&args[(colon + 1)..]).map_err(|err| parse_error(err, offset + colon + 1))?;
+
+        let (mut scratch, actual_id) = self.infer(&expr)?;
+
+        // The expected type is built alongside the copy of the actual one, so that checking does
+        // not add states to the session.
+        let expected_id = build_polar(&mut scratch, &expected).map_err(|err| err.to_string())?;
+
+        let mut reduced = Automaton::new();
+        let ids: Vec<_> = reduced
+            .reduce(
+                &scratch,
+                vec![(actual_id, Polarity::Pos), (expected_id, Polarity::Pos)],
+            )
+            .collect();
+        if reduced.subsume(ids[0], ids[1], Polarity::Pos) {
+            Ok("ok".to_owned())
+        } else {
+            let actual: Ty<Constructed<usize>, usize> =
+                scratch.to_polar_ty(actual_id, Polarity::Pos);
+            Err(format!(
+                "expected {}, found {}",
+                expected.display(Polarity::Pos),
+                actual.display(Polarity::Pos)
+            ))
+        }
+    }
+
+    // Prints the states reachable from the simplified type of an expression, along with their
+    // constructors and flow edges.
+    fn auto(&mut self, args: &str, offset: usize) -> Result<String, String> {
+        let expr = parse_expr(args).map_err(|err| parse_error(err, offset))?;
+        let (auto, root) = self.infer(&expr)?;
+        Ok(dump(&auto, root))
+    }
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Repl::new()
+    }
+}
+
+// Copies the states reachable from a positive state into a new automaton, simplifying them.
+fn simplify(auto: &Automaton<Constructor>, ty: StateId) -> (Automaton<Constructor>, StateId) {
+    let mut reduced = Automaton::new();
+    let range = reduced.reduce(auto, once((ty, Polarity::Pos)));
+    let mut minimized = Automaton::new();
+    let root = minimized
+        .minimize(&reduced, range.zip(once(Polarity::Pos)))
+        .next()
+        .unwrap();
+    (minimized, root)
+}
+
+fn parse_error(err: ParseError, offset: usize) -> String {
+    format!(
+        "{} at {}..{}",
+        err.message,
+        err.span.start + offset,
+        err.span.end + offset
+    )
+}
+
+// Builds a polar type, using a fresh type variable for each distinct name.
+fn build_polar(
+    auto: &mut Automaton<Constructor>,
+    ty: &Ty<Constructed<String>, String>,
+) -> Result<StateId, ValidateError> {
+    let ty = bind_vars(auto, &mut HashMap::new(), ty);
+    auto.build_polar_simple(Polarity::Pos, &ty)
+}
+
+type Bound = Ty<Constructed<flow::Pair>, flow::Pair>;
+
+fn bind_vars(
+    auto: &mut Automaton<Constructor>,
+    vars: &mut HashMap<String, flow::Pair>,
+    ty: &Ty<Constructed<String>, String>,
+) -> Bound {
+    let mut bind = |ty| Box::new(bind_vars(auto, vars, ty));
+    match ty {
+        Ty::Zero => Ty::Zero,
+        Ty::Add(l, r) => {
+            let l = bind(l);
+            Ty::Add(l, bind(r))
+        }
+        Ty::UnboundVar(name) => match vars.get(name) {
+            Some(&pair) => Ty::UnboundVar(pair),
+            None => {
+                let pair = auto.build_var();
+                vars.insert(name.clone(), pair);
+                Ty::UnboundVar(pair)
+            }
+        },
+        Ty::BoundVar(idx) => Ty::BoundVar(*idx),
+        Ty::Constructed(con) => Ty::Constructed(match con {
+            Constructed::Base(name) => Constructed::Base(name.clone()),
+            Constructed::Fun(d, r) => {
+                let d = bind(d);
+                Constructed::Fun(d, bind(r))
+            }
+            Constructed::Record(fields) => Constructed::Record(
+                fields
+                    .iter()
+                    .map(|(label, ty)| (label.clone(), *bind(ty)))
+                    .collect(),
+            ),
+            Constructed::Variant(tags) => Constructed::Variant(
+                tags.iter()
+                    .map(|(label, ty)| (label.clone(), *bind(ty)))
+                    .collect(),
+            ),
+            Constructed::Tuple(elems) => {
+                Constructed::Tuple(elems.iter().map(|ty| *bind(ty)).collect())
+            }
+            Constructed::Ref(r, w) => {
+                let r = bind(r);
+                Constructed::Ref(r, bind(w))
+            }
+        }),
+        Ty::Recursive(ty) => Ty::Recursive(bind(ty)),
+    }
+}
+
+// Prints each state reachable from `root` in breadth-first order, such as `2- {x: 3} ~ 4`
+// for a negative state requiring a record with field `x` of type `3`, and a flow edge to `4`.
+fn dump(auto: &Automaton<Constructor>, root: StateId) -> String {
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert(root);
+    queue.push_back((root, Polarity::Pos));
+
+    let mut output = String::new();
+    while let Some((id, pol)) = queue.pop_front() {
+        let state = &auto[id];
+        let (sign, empty, sep) = match pol {
+            Polarity::Pos => ('+', "⊥", " ⊔ "),
+            Polarity::Neg => ('-', "⊤", " ⊓ "),
+        };
+
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        write!(output, "{}{} ", id.as_u32(), sign).unwrap();
+        let cons: Vec<_> = state
+            .constructors()
+            .iter()
+            .map(|con| {
+                con.visit_params(|label, set| {
+                    for child in set.iter() {
+                        if seen.insert(child) {
+                            queue.push_back((child, pol * label.polarity()));
+                        }
+                    }
+                });
+                fmt_constructor(con)
+            })
+            .collect();
+        if cons.is_empty() {
+            output.push_str(empty);
+        } else {
+            output.push_str(&cons.join(sep));
+        }
+
+        let mut flow: Vec<_> = state.flow().iter().map(StateId::as_u32).collect();
+        flow.sort_unstable();
+        for (idx, other) in flow.into_iter().enumerate() {
+            output.push_str(if idx == 0 { " ~ " } else { ", " });
+            write!(output, "{}", other).unwrap();
+        }
+    }
+    output
+}
+
+fn fmt_constructor(con: &Constructor) -> String {
+    match con {
+        Constructor::Base(name) => name.to_string(),
+        Constructor::Fun(d, r) => format!("{} -> {}", fmt_set(d), fmt_set(r)),
+        Constructor::Record(fields) => format!("{{{}}}", fmt_fields(fields.iter())),
+        Constructor::Variant(tags) => format!("[{}]", fmt_fields(tags.iter())),
+        Constructor::Tuple(elems) if elems.len() == 1 => format!("({},)", fmt_set(&elems[0])),
+        Constructor::Tuple(elems) => format!(
+            "({})",
+            elems.iter().map(fmt_set).collect::<Vec<_>>().join(", ")
+        ),
+        Constructor::Ref(r, w) => format!("ref<{}, {}>", fmt_set(r), fmt_set(w)),
+    }
+}
+
+fn fmt_fields<'a, I>(fields: I) -> String
+where
+    I: Iterator<Item = (&'a Rc<str>, &'a StateSet)>,
+{
+    fields
+        .map(|(label, set)| format!("{}: {}", label, fmt_set(set)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn fmt_set(set: &StateSet) -> String {
+    set.iter()
+        .map(|id| id.as_u32().to_string())
+        .collect::<Vec<_>>()
+        .join("|")
+}
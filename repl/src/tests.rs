@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use mlsub::infer::{Expr, Lit};
+
+use crate::parse::{parse_expr, parse_line, Line};
+use crate::repl::{Output, Repl};
+
+fn eval(repl: &mut Repl, line: &str) -> Result<String, String> {
+    match repl.eval(line)? {
+        Output::Print(output) => Ok(output),
+        Output::Quit => panic!("unexpected quit"),
+    }
+}
+
+fn var(name: &str) -> Expr {
+    Expr::Var(Rc::from(name))
+}
+
+#[test]
+fn parse() {
+    assert_eq!(
+        parse_expr("fun f -> f x.y (g 1)"),
+        Ok(Expr::Lambda(
+            Rc::from("f"),
+            Box::new(Expr::App(
+                Box::new(Expr::App(
+                    Box::new(var("f")),
+                    Box::new(Expr::Proj(Box::new(var("x")), Rc::from("y")))
+                )),
+                Box::new(Expr::App(
+                    Box::new(var("g")),
+                    Box::new(Expr::Lit(Lit::Int(1)))
+                )),
+            ))
+        ))
+    );
+    assert_eq!(
+        parse_expr("if true then {} else {x = let y = 1 in y}"),
+        Ok(Expr::If(
+            Box::new(Expr::Lit(Lit::Bool(true))),
+            Box::new(Expr::Record(BTreeMap::new())),
+            Box::new(Expr::Record(
+                vec![(
+                    Rc::from("x"),
+                    Expr::Let(
+                        Rc::from("y"),
+                        Box::new(Expr::Lit(Lit::Int(1))),
+                        Box::new(var("y"))
+                    )
+                )]
+                .into_iter()
+                .collect()
+            )),
+        ))
+    );
+    assert_eq!(
+        parse_line("let x = y"),
+        Ok(Line::Define(Rc::from("x"), var("y")))
+    );
+    assert_eq!(
+        parse_line("let x = y in x"),
+        Ok(Line::Expr(Expr::Let(
+            Rc::from("x"),
+            Box::new(var("y")),
+            Box::new(var("x"))
+        )))
+    );
+    assert_eq!(parse_expr("letter"), Ok(var("letter")));
+    assert!(parse_expr("fun then -> 1").is_err());
+    assert!(parse_expr("(x").is_err());
+    assert!(parse_expr("x)").is_err());
+}
+
+#[test]
+fn infer() {
+    let mut repl = Repl::new();
+    assert_eq!(eval(&mut repl, "fun x -> x"), Ok("'a -> 'a".to_owned()));
+    assert_eq!(
+        eval(&mut repl, "let id = fun x -> x"),
+        Ok("id : 'a -> 'a".to_owned())
+    );
+    assert_eq!(
+        eval(&mut repl, "{a = id 1, b = id true}"),
+        Ok("{a: int, b: bool}".to_owned())
+    );
+    assert_eq!(
+        eval(&mut repl, "if 1 then 2 else 3"),
+        Err("expected bool, found int".to_owned())
+    );
+    assert_eq!(eval(&mut repl, "  "), Ok(String::new()));
+}
+
+#[test]
+fn check() {
+    let mut repl = Repl::new();
+    assert_eq!(
+        eval(&mut repl, ":check fun x -> x : int -> int"),
+        Ok("ok".to_owned())
+    );
+    assert_eq!(
+        eval(&mut repl, ":check fun r -> r.x : {x: int, y: bool} -> int"),
+        Ok("ok".to_owned())
+    );
+    assert_eq!(
+        eval(&mut repl, ":check fun x -> x : int -> bool"),
+        Err("expected int -> bool, found 'a -> 'a".to_owned())
+    );
+    assert_eq!(
+        eval(&mut repl, ":check fun x -> 1 : 'a -> 'a"),
+        Err("expected 'a -> 'a, found ⊤ -> int".to_owned())
+    );
+    assert_eq!(
+        eval(&mut repl, ":check 1 : {x: }"),
+        Err("expected a type at 15..16".to_owned())
+    );
+    assert_eq!(
+        eval(&mut repl, ":check 1"),
+        Err("expected `:check e : T`".to_owned())
+    );
+}
+
+#[test]
+fn auto() {
+    let mut repl = Repl::new();
+    assert_eq!(
+        eval(&mut repl, ":auto {x = 1, y = fun x -> x}"),
+        Ok("0+ {x: 4, y: 1}\n4+ int\n1+ 3 -> 2\n3- ⊤ ~ 2\n2+ ⊥ ~ 3".to_owned())
+    );
+}
+
+#[test]
+fn commands() {
+    let mut repl = Repl::new();
+    assert!(matches!(repl.eval(":quit"), Ok(Output::Quit)));
+    assert!(matches!(repl.eval(":help"), Ok(Output::Print(_))));
+    assert_eq!(
+        eval(&mut repl, ":foo"),
+        Err("unknown command `:foo`, try `:help`".to_owned())
+    );
+    assert_eq!(
+        eval(&mut repl, "(fun x -> x"),
+        Err("expected `)` at 11..11".to_owned())
+    );
+}
@@ -0,0 +1,107 @@
+//! [`proptest`] strategies for generating polar types and constraints, enabled by the `arbitrary`
+//! feature.
+//!
+//! The strategies are generic over the constructor type: callers supply a function which creates
+//! a strategy for constructors given a strategy for their parameters.
+
+use std::fmt::Debug;
+
+use proptest::prelude::*;
+use proptest::prop_oneof;
+use proptest::strategy::{LazyJust, NewTree, ValueTree};
+use proptest::test_runner::TestRunner;
+use rand_distr::Exp1;
+
+use crate::auto::Build;
+use crate::polar::Ty;
+use crate::{Constructor, Polarity};
+
+/// Generates valid polar types of the given polarity, with type variables named `'a'` to `'e'`.
+pub fn arb_polar_ty<B, C, F, S>(pol: Polarity, cons: F) -> BoxedStrategy<Ty<B, char>>
+where
+    B: Build<C, char> + Clone + Debug + 'static,
+    C: Constructor,
+    F: Fn(BoxedStrategy<Ty<B, char>>) -> S + 'static,
+    S: Strategy<Value = B> + 'static,
+{
+    prop_oneof![
+        LazyJust::new(|| Ty::Zero),
+        prop::char::range('a', 'e').prop_map(Ty::UnboundVar),
+        BoundVar.prop_map(Ty::BoundVar),
+    ]
+    .prop_recursive(32, 1000, 8, move |inner| {
+        prop_oneof![
+            3 => cons(inner.clone()).prop_map(Ty::Constructed),
+            1 => (inner.clone(), inner.clone()).prop_map(|(l, r)| Ty::Add(Box::new(l), Box::new(r))),
+            1 => inner.prop_map(Box::new).prop_map(Ty::Recursive),
+        ]
+    })
+    .prop_filter("invalid polar type", move |ty| ty.validate(pol).is_ok())
+    .boxed()
+}
+
+/// Generates constraints t⁺ ≤ t⁻ between a positive and a negative type.
+pub fn arb_constraint<B, C, F, S>(cons: F) -> BoxedStrategy<(Ty<B, char>, Ty<B, char>)>
+where
+    B: Build<C, char> + Clone + Debug + 'static,
+    C: Constructor,
+    F: Fn(BoxedStrategy<Ty<B, char>>) -> S + Clone + 'static,
+    S: Strategy<Value = B> + 'static,
+{
+    (
+        arb_polar_ty(Polarity::Pos, cons.clone()),
+        arb_polar_ty(Polarity::Neg, cons),
+    )
+        .boxed()
+}
+
+/// Generates constructors from [`std_cons`](crate::std_cons): the base type `bool`, functions and
+/// records with single letter labels.
+#[cfg(feature = "std_cons")]
+pub fn arb_std_cons(
+    ty: BoxedStrategy<Ty<crate::std_cons::Constructed<char>, char>>,
+) -> BoxedStrategy<crate::std_cons::Constructed<char>> {
+    use proptest::collection::btree_map;
+
+    use crate::std_cons::Constructed;
+
+    let label = prop::char::range('a', 'z').prop_map(|ch| ch.to_string().into());
+    prop_oneof![
+        LazyJust::new(|| Constructed::Base("bool".into())),
+        (ty.clone(), ty.clone()).prop_map(|(d, r)| Constructed::Fun(Box::new(d), Box::new(r))),
+        btree_map(label, ty, 0..8).prop_map(Constructed::Record),
+    ]
+    .boxed()
+}
+
+// Generates de Bruijn indices for bound variables. Small indices are the most likely to be valid,
+// so they are drawn from an exponential distribution.
+#[derive(Debug)]
+struct BoundVar;
+struct BoundVarTree(usize);
+
+impl Strategy for BoundVar {
+    type Tree = BoundVarTree;
+    type Value = usize;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let val: f64 = runner.rng().sample(Exp1);
+        Ok(BoundVarTree(val as usize))
+    }
+}
+
+impl ValueTree for BoundVarTree {
+    type Value = usize;
+
+    fn current(&self) -> Self::Value {
+        self.0
+    }
+
+    fn simplify(&mut self) -> bool {
+        false
+    }
+
+    fn complicate(&mut self) -> bool {
+        false
+    }
+}
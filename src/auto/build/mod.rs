@@ -1,13 +1,17 @@
 pub(crate) mod polar;
 
+mod provenance;
+
 pub use self::polar::Build;
+pub use self::provenance::Provenance;
 
 use std::borrow::Cow;
 
 use crate::auto::{flow, Automaton, State, StateId};
+use crate::polar::ValidateError;
 use crate::{Constructor, Polarity};
 
-impl<'a, C: Constructor> Automaton<C> {
+impl<C: Constructor> Automaton<C> {
     /// Build an empty state, representing the bottom and top types for positive and negative
     /// polarities respectively.
     pub fn build_empty(&mut self, pol: Polarity) -> StateId {
@@ -54,14 +58,17 @@ impl<'a, C: Constructor> Automaton<C> {
         self[at].cons.add(pol, Cow::Owned(con));
     }
 
+    /// Build a polar type whose type variables are already flow edges in this automaton,
+    /// returning an error if it is not [well-formed](crate::polar::Ty::validate).
     pub fn build_polar_simple<B>(
         &mut self,
         pol: Polarity,
         ty: &crate::polar::Ty<B, flow::Pair>,
-    ) -> StateId
+    ) -> Result<StateId, ValidateError>
     where
         B: Build<C, flow::Pair>,
     {
-        self.simple_builder().build_polar(pol, ty)
+        ty.validate(pol)?;
+        Ok(self.simple_builder().build_polar(pol, ty))
     }
 }
@@ -12,12 +12,20 @@ pub trait Build<C: Constructor, V>: Sized {
     where
         V: 'a,
         F: FnMut(C::Label, &'a polar::Ty<Self, V>) -> StateSet;
+
+    /// Visits the type parameters of this constructor, along with their labels.
+    fn visit_params<'a, F>(&'a self, visit: F)
+    where
+        V: 'a,
+        F: FnMut(C::Label, &'a polar::Ty<Self, V>);
 }
 
 pub(crate) trait BuildVar<V> {
     fn build_var<C: Constructor>(&mut self, auto: &mut Automaton<C>, var: V) -> flow::Pair;
 }
 
+type Stack<'b, B, V> = Vec<(Polarity, StateId, &'b polar::Ty<B, V>, Vector<StateId>)>;
+
 pub(crate) struct Builder<'a, C, W>
 where
     C: Constructor,
@@ -30,14 +38,14 @@ impl<'a, C: Constructor> Automaton<C> {
     #[cfg(test)]
     pub(crate) fn builder<V: Eq + Hash + Clone>(
         &'a mut self,
-    ) -> Builder<C, HashMap<V, flow::Pair>> {
+    ) -> Builder<'a, C, HashMap<V, flow::Pair>> {
         Builder {
             auto: self,
             vars: HashMap::new(),
         }
     }
 
-    pub(crate) fn simple_builder(&'a mut self) -> Builder<C, ()> {
+    pub(crate) fn simple_builder(&'a mut self) -> Builder<'a, C, ()> {
         Builder {
             auto: self,
             vars: (),
@@ -55,6 +63,8 @@ where
         V: Clone,
         W: BuildVar<V>,
     {
+        debug_assert_eq!(ty.validate(pol), Ok(()));
+
         let at = self.auto.build_empty(pol);
         let mut stack = vec![(pol, at, ty, Vector::new())];
         while let Some((pol, at, ty, mut recs)) = stack.pop() {
@@ -68,7 +78,7 @@ where
         pol: Polarity,
         at: StateId,
         ty: &'b polar::Ty<B, V>,
-        stack: &mut Vec<(Polarity, StateId, &'b polar::Ty<B, V>, Vector<StateId>)>,
+        stack: &mut Stack<'b, B, V>,
         recs: &mut Vector<StateId>,
     ) where
         B: Build<C, V>,
@@ -77,7 +87,6 @@ where
     {
         // TODO produce less garbage states
 
-        #[cfg(debug_assertions)]
         debug_assert_eq!(self.auto[at].pol, pol);
 
         match ty {
@@ -120,7 +129,7 @@ where
         pol: Polarity,
         epsilon: bool,
         ty: &'b polar::Ty<B, V>,
-        stack: &mut Vec<(Polarity, StateId, &'b polar::Ty<B, V>, Vector<StateId>)>,
+        stack: &mut Stack<'b, B, V>,
         recs: &mut Vector<StateId>,
     ) -> StateId
     where
@@ -147,7 +156,7 @@ where
     V: Eq + Hash + Clone,
 {
     fn build_var<C: Constructor>(&mut self, auto: &mut Automaton<C>, var: V) -> flow::Pair {
-        self.entry(var).or_insert_with(|| auto.build_var()).clone()
+        *self.entry(var).or_insert_with(|| auto.build_var())
     }
 }
 
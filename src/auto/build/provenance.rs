@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use crate::auto::{Automaton, StateId};
+use crate::Constructor;
+
+/// Associates user data, such as the source location of an expression, with the states built
+/// for it, so that [biunification errors](crate::BiunifyError::provenance) can refer back to it.
+#[derive(Debug, Clone)]
+pub struct Provenance<P> {
+    states: HashMap<StateId, P>,
+}
+
+impl<P> Provenance<P> {
+    pub fn new() -> Self {
+        Provenance {
+            states: HashMap::new(),
+        }
+    }
+
+    /// Gets the provenance recorded for a state.
+    pub fn get(&self, id: StateId) -> Option<&P> {
+        self.states.get(&id)
+    }
+}
+
+impl<P: Clone> Provenance<P> {
+    /// Runs `build`, and records `provenance` for each state it adds to `auto`.
+    ///
+    /// Calls may be nested, in which case states keep the provenance of the innermost call
+    /// that built them.
+    pub fn record<C, T, F>(&mut self, auto: &mut Automaton<C>, provenance: P, build: F) -> T
+    where
+        C: Constructor,
+        F: FnOnce(&mut Automaton<C>, &mut Self) -> T,
+    {
+        let start = auto.next();
+        let result = build(auto, self);
+        for id in auto.range_from(start) {
+            self.states.entry(id).or_insert_with(|| provenance.clone());
+        }
+        result
+    }
+}
+
+impl<P> Default for Provenance<P> {
+    fn default() -> Self {
+        Provenance::new()
+    }
+}
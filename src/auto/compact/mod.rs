@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests;
+
+use std::mem::take;
+
+use crate::auto::{Automaton, State, StateId};
+use crate::biunify::{CacheEntry, Side};
+use crate::Constructor;
+
+/// Maps the ids of states before a call to [`Automaton::compact`] to their new ids.
+#[derive(Debug, Clone)]
+pub struct Remap {
+    ids: Vec<Option<StateId>>,
+}
+
+impl Remap {
+    /// Gets the new id of a state, or `None` if it was removed.
+    pub fn get(&self, StateId(id): StateId) -> Option<StateId> {
+        self.ids.get(id as usize).copied().flatten()
+    }
+}
+
+impl<C: Constructor> Automaton<C> {
+    /// Removes all states not reachable from `roots`, returning the new ids of the remaining
+    /// states.
+    ///
+    /// Flow edges to removed states are dropped, as are cached constraints involving them.
+    ///
+    /// This renumbers states, so it must not be called during a
+    /// [`transaction`](Automaton::transaction).
+    pub fn compact<I>(&mut self, roots: I) -> Remap
+    where
+        I: IntoIterator<Item = StateId>,
+    {
+        debug_assert!(self.journals.is_empty());
+
+        let mut reachable = vec![false; self.states.len()];
+        let mut stack: Vec<_> = roots.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            if !reachable[id.0 as usize] {
+                reachable[id.0 as usize] = true;
+                for con in self[id].cons.iter() {
+                    con.visit_params(|_, set| stack.extend(set));
+                }
+            }
+        }
+
+        // Remaining states keep their relative order.
+        let mut next = 0;
+        let remap = Remap {
+            ids: reachable
+                .iter()
+                .map(|&keep| {
+                    if keep {
+                        next += 1;
+                        Some(StateId(next - 1))
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+        };
+
+        let states = take(&mut self.states);
+        self.states = states
+            .into_iter()
+            .zip(&reachable)
+            .filter(|&(_, &keep)| keep)
+            .map(|(state, _)| State {
+                pol: state.pol,
+                cons: state.cons.remap(|id| remap.get(id).unwrap()),
+                flow: state.flow.filter_map(|id| remap.get(id)),
+            })
+            .collect();
+
+        let biunify_cache = take(&mut self.biunify_cache);
+        self.biunify_cache = biunify_cache
+            .into_iter()
+            .filter_map(|((qp, qn), entry)| {
+                let key = (remap.get(qp)?, remap.get(qn)?);
+                Some((key, remap.cache_entry(entry)))
+            })
+            .collect();
+
+        #[cfg(debug_assertions)]
+        debug_assert!(self.check_flow());
+
+        remap
+    }
+}
+
+impl Remap {
+    // The constraint that required a cached entry may have been removed, in which case the
+    // entry is treated as a root.
+    fn cache_entry<C: Constructor>(&self, entry: CacheEntry<C>) -> CacheEntry<C> {
+        match entry {
+            CacheEntry::Root => CacheEntry::Root,
+            CacheEntry::RequiredBy { label, pos, neg } => {
+                match (self.constructor(pos), self.constructor(neg)) {
+                    (Some(pos), Some(neg)) => CacheEntry::RequiredBy { label, pos, neg },
+                    _ => CacheEntry::Root,
+                }
+            }
+        }
+    }
+
+    fn constructor<C: Constructor>(&self, Side { id, con }: Side<C>) -> Option<Side<C>> {
+        let id = self.get(id)?;
+        let mut kept = true;
+        con.visit_params(|_, set| kept &= set.iter().all(|id| self.get(id).is_some()));
+        if !kept {
+            return None;
+        }
+        let con = con.map(|_, set| set.remap(|id| self.get(id).unwrap()));
+        Some(Side { id, con })
+    }
+}
@@ -0,0 +1,141 @@
+use std::iter::once;
+
+use proptest::test_runner::Config;
+use proptest::{prop_assert, prop_assert_eq, proptest};
+
+use crate::auto::{Automaton, StateId};
+use crate::polar::Ty;
+use crate::tests::{arb_polar_ty, Constructed, Constructor};
+use crate::Polarity;
+
+fn build(auto: &mut Automaton<Constructor>, ty: &Ty<Constructed, char>, pol: Polarity) -> StateId {
+    auto.builder().build_polar(pol, ty)
+}
+
+fn fun(d: Ty<Constructed, char>, r: Ty<Constructed, char>) -> Ty<Constructed, char> {
+    Ty::Constructed(Constructed::Fun(Box::new(d), Box::new(r)))
+}
+
+fn check_compact(
+    ty: &Ty<Constructed, char>,
+    garbage: &Ty<Constructed, char>,
+    pol: Polarity,
+) -> bool {
+    let mut auto = Automaton::new();
+    build(&mut auto, garbage, pol);
+    let id = build(&mut auto, ty, pol);
+    build(&mut auto, garbage, pol);
+
+    let expected: Ty<Constructed<usize>, usize> = auto.to_polar_ty(id, pol);
+    let len = auto.states.len();
+    let remap = auto.compact(once(id));
+    let id = remap.get(id).unwrap();
+
+    auto.states.len() < len && auto.to_polar_ty(id, pol) == expected
+}
+
+#[test]
+fn compact_unreachable() {
+    let mut auto = Automaton::new();
+    let a = build(
+        &mut auto,
+        &Ty::Constructed(Constructed::Bool),
+        Polarity::Pos,
+    );
+    let b = build(&mut auto, &fun(Ty::Zero, Ty::Zero), Polarity::Pos);
+    let c = build(
+        &mut auto,
+        &Ty::Constructed(Constructed::Bool),
+        Polarity::Pos,
+    );
+
+    let remap = auto.compact(vec![c, a]);
+    assert_eq!(auto.states.len(), 2);
+    assert_eq!(remap.get(a), Some(StateId(0)));
+    assert_eq!(remap.get(b), None);
+    assert_eq!(remap.get(c), Some(StateId(1)));
+}
+
+#[test]
+fn compact_flow() {
+    // 'a → 'a
+    let ty = fun(Ty::UnboundVar('a'), Ty::UnboundVar('a'));
+
+    let mut auto = Automaton::new();
+    let mut builder = auto.builder();
+    let id = builder.build_polar(Polarity::Pos, &ty);
+    let other = builder.build_polar(Polarity::Pos, &ty);
+    drop(builder);
+
+    let remap = auto.compact(once(id));
+    assert_eq!(remap.get(other), None);
+    #[cfg(debug_assertions)]
+    assert!(auto.check_flow());
+    assert_eq!(
+        auto.to_polar_ty::<Constructed<usize>, usize>(remap.get(id).unwrap(), Polarity::Pos),
+        Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::UnboundVar(0)),
+            Box::new(Ty::UnboundVar(0)),
+        ))
+    );
+}
+
+#[test]
+fn compact_biunify_cache() {
+    let mut auto = Automaton::new();
+    let lhs = build(
+        &mut auto,
+        &fun(Ty::Zero, Ty::UnboundVar('a')),
+        Polarity::Pos,
+    );
+    let rhs = build(
+        &mut auto,
+        &fun(Ty::Constructed(Constructed::Bool), Ty::UnboundVar('b')),
+        Polarity::Neg,
+    );
+    let other_lhs = build(
+        &mut auto,
+        &Ty::Constructed(Constructed::Bool),
+        Polarity::Pos,
+    );
+    let other_rhs = build(&mut auto, &Ty::UnboundVar('c'), Polarity::Neg);
+    auto.biunify(lhs, rhs).unwrap();
+    auto.biunify(other_lhs, other_rhs).unwrap();
+    assert_eq!(auto.biunify_cache.len(), 4);
+
+    let remap = auto.compact(vec![lhs, rhs]);
+    assert_eq!(auto.biunify_cache.len(), 3);
+    let (lhs, rhs) = (remap.get(lhs).unwrap(), remap.get(rhs).unwrap());
+    assert!(auto.biunify_cache.contains_key(&(lhs, rhs)));
+    assert!(auto.biunify(lhs, rhs).is_ok());
+}
+
+proptest! {
+    #![proptest_config(Config {
+        cases: 1024,
+        timeout: 10000,
+        ..Config::default()
+    })]
+
+    #[test]
+    fn compact_pos(ty in arb_polar_ty(Polarity::Pos), garbage in arb_polar_ty(Polarity::Pos)) {
+        prop_assert!(check_compact(&ty, &garbage, Polarity::Pos));
+    }
+
+    #[test]
+    fn compact_neg(ty in arb_polar_ty(Polarity::Neg), garbage in arb_polar_ty(Polarity::Neg)) {
+        prop_assert!(check_compact(&ty, &garbage, Polarity::Neg));
+    }
+
+    #[test]
+    fn compact_cloned(ty in arb_polar_ty(Polarity::Pos)) {
+        let mut auto = Automaton::new();
+        let id = build(&mut auto, &ty, Polarity::Pos);
+        let range = auto.clone_states(once((id, Polarity::Pos)));
+        let len = auto.states.len() - range.clone().next().unwrap().as_u32() as usize;
+
+        let remap = auto.compact(range);
+        prop_assert_eq!(auto.states.len(), len);
+        prop_assert_eq!(remap.get(id), None);
+    }
+}
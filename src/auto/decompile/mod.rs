@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::auto::{Automaton, StateId, StateSet};
+use crate::polar;
+use crate::{Constructor, Label, Polarity};
+
+/// The inverse of [`Build`](crate::auto::Build), converting automaton constructors back into
+/// polar type constructors.
+pub trait Decompile<C: Constructor, V>: Sized {
+    fn decompile<F>(con: &C, mapper: F) -> Self
+    where
+        F: FnMut(C::Label, &StateSet) -> polar::Ty<Self, V>;
+}
+
+struct Decompiler<'a, C: Constructor> {
+    auto: &'a Automaton<C>,
+    // Polarity of each state reachable from the root.
+    pols: HashMap<StateId, Polarity>,
+    // Type variables occurring in each state.
+    vars: HashMap<StateId, Vec<usize>>,
+    // States currently being decompiled, and whether they are bound by a recursive type.
+    stack: Vec<(StateId, bool)>,
+}
+
+impl<C: Constructor> Automaton<C> {
+    /// Converts the type represented by a state back into a polar type.
+    ///
+    /// Cycles in the automaton become recursive types, and each group of states sharing the
+    /// same flow edges becomes a fresh type variable, numbered from zero.
+    pub fn to_polar_ty<B, V>(&self, id: StateId, pol: Polarity) -> polar::Ty<B, V>
+    where
+        B: Decompile<C, V>,
+        V: From<usize>,
+    {
+        let mut decompiler = Decompiler {
+            auto: self,
+            pols: HashMap::new(),
+            vars: HashMap::new(),
+            stack: Vec::new(),
+        };
+        decompiler.find_reachable(id, pol);
+        decompiler.find_vars();
+        decompiler.decompile(id)
+    }
+}
+
+impl<'a, C: Constructor> Decompiler<'a, C> {
+    fn find_reachable(&mut self, id: StateId, pol: Polarity) {
+        let mut stack = vec![(id, pol)];
+        while let Some((id, pol)) = stack.pop() {
+            debug_assert_eq!(self.auto[id].pol, pol);
+
+            if self.pols.insert(id, pol).is_none() {
+                for con in self.auto[id].cons.iter() {
+                    con.visit_params(|label, set| {
+                        stack.extend(set.iter().map(|id| (id, pol * label.polarity())));
+                    });
+                }
+            }
+        }
+    }
+
+    fn find_vars(&mut self) {
+        let mut neg_ids: Vec<_> = self
+            .pols
+            .iter()
+            .filter(|&(_, &pol)| pol == Polarity::Neg)
+            .map(|(&id, _)| id)
+            .collect();
+        neg_ids.sort();
+
+        // Negative states with the same set of reachable flow edges share a variable.
+        let mut classes = HashMap::new();
+        for neg in neg_ids {
+            let mut flow: Vec<_> = self.auto[neg]
+                .flow
+                .iter()
+                .filter(|pos| self.pols.contains_key(pos))
+                .collect();
+            if flow.is_empty() {
+                continue;
+            }
+            flow.sort();
+
+            let next = classes.len();
+            let var = *classes.entry(flow.clone()).or_insert(next);
+            self.vars.entry(neg).or_default().push(var);
+            if var == next {
+                for pos in flow {
+                    self.vars.entry(pos).or_default().push(var);
+                }
+            }
+        }
+    }
+
+    fn decompile<B, V>(&mut self, id: StateId) -> polar::Ty<B, V>
+    where
+        B: Decompile<C, V>,
+        V: From<usize>,
+    {
+        if let Some(idx) = self.stack.iter().rposition(|&(st, _)| st == id) {
+            debug_assert!(self.stack[idx].1);
+            let binders = self.stack[idx + 1..].iter().filter(|&&(_, rec)| rec);
+            return polar::Ty::BoundVar(binders.count());
+        }
+
+        let rec = self.is_recursive(id);
+        self.stack.push((id, rec));
+
+        let auto = self.auto;
+        let vars = self
+            .vars
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(|&var| polar::Ty::UnboundVar(V::from(var)))
+            .collect::<Vec<_>>();
+        let cons = auto[id]
+            .cons
+            .iter()
+            .map(|con| polar::Ty::Constructed(B::decompile(con, |_, set| self.decompile_set(set))))
+            .collect::<Vec<_>>();
+        let ty = add(vars.into_iter().chain(cons));
+
+        self.stack.pop();
+        if rec {
+            polar::Ty::Recursive(Box::new(ty))
+        } else {
+            ty
+        }
+    }
+
+    fn decompile_set<B, V>(&mut self, set: &StateSet) -> polar::Ty<B, V>
+    where
+        B: Decompile<C, V>,
+        V: From<usize>,
+    {
+        add(set.iter().map(|id| self.decompile(id)).collect::<Vec<_>>())
+    }
+
+    /// Whether a state can reach itself without passing through a state currently being
+    /// decompiled, in which case it must be bound by a recursive type.
+    fn is_recursive(&self, id: StateId) -> bool {
+        let mut visited: HashSet<_> = self.stack.iter().map(|&(st, _)| st).collect();
+        let mut stack = vec![id];
+        let mut found = false;
+        while let Some(st) = stack.pop() {
+            for con in self.auto[st].cons.iter() {
+                con.visit_params(|_, set| {
+                    for next in set.iter() {
+                        if next == id {
+                            found = true;
+                        } else if visited.insert(next) {
+                            stack.push(next);
+                        }
+                    }
+                });
+            }
+            if found {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+fn add<B, V, I>(tys: I) -> polar::Ty<B, V>
+where
+    I: IntoIterator<Item = polar::Ty<B, V>>,
+{
+    tys.into_iter()
+        .fold(None, |acc, ty| match acc {
+            None => Some(ty),
+            Some(acc) => Some(polar::Ty::Add(Box::new(acc), Box::new(ty))),
+        })
+        .unwrap_or(polar::Ty::Zero)
+}
@@ -0,0 +1,124 @@
+use std::hash::Hash;
+use std::iter::once;
+
+use proptest::test_runner::Config;
+use proptest::{prop_assert, proptest};
+
+use crate::auto::{Automaton, StateId};
+use crate::polar::Ty;
+use crate::tests::{arb_polar_ty, Constructed, Constructor};
+use crate::Polarity;
+
+fn decompile(ty: &Ty<Constructed, char>, pol: Polarity) -> Ty<Constructed<usize>, usize> {
+    let mut nfa = Automaton::new();
+    let id = nfa.builder().build_polar(pol, ty);
+
+    let mut dfa = Automaton::new();
+    let id = dfa.reduce(&nfa, once((id, pol))).next().unwrap();
+    dfa.to_polar_ty(id, pol)
+}
+
+fn build<V>(auto: &mut Automaton<Constructor>, ty: &Ty<Constructed<V>, V>, pol: Polarity) -> StateId
+where
+    V: Eq + Hash + Clone,
+{
+    let mut builder = auto.builder::<V>();
+    builder.build_polar(pol, ty)
+}
+
+#[test]
+fn decompile_var() {
+    let ty = Ty::Constructed(Constructed::Fun(
+        Box::new(Ty::UnboundVar('a')),
+        Box::new(Ty::UnboundVar('a')),
+    ));
+
+    assert_eq!(
+        decompile(&ty, Polarity::Pos),
+        Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::UnboundVar(0)),
+            Box::new(Ty::UnboundVar(0)),
+        ))
+    );
+}
+
+#[test]
+fn decompile_unused_var() {
+    let ty = Ty::Constructed(Constructed::Fun(
+        Box::new(Ty::UnboundVar('a')),
+        Box::new(Ty::UnboundVar('b')),
+    ));
+
+    assert_eq!(
+        decompile(&ty, Polarity::Pos),
+        Ty::Constructed(Constructed::Fun(Box::new(Ty::Zero), Box::new(Ty::Zero)))
+    );
+}
+
+#[test]
+fn decompile_recursive() {
+    let ty = Ty::Recursive(Box::new(Ty::Constructed(Constructed::Fun(
+        Box::new(Ty::Constructed(Constructed::Bool)),
+        Box::new(Ty::BoundVar(0)),
+    ))));
+
+    assert_eq!(
+        decompile(&ty, Polarity::Pos),
+        Ty::Recursive(Box::new(Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::Constructed(Constructed::Bool)),
+            Box::new(Ty::BoundVar(0)),
+        ))))
+    );
+}
+
+#[test]
+fn decompile_nested_recursive() {
+    // rec a. rec b. a → b
+    let ty = Ty::Recursive(Box::new(Ty::Constructed(Constructed::Fun(
+        Box::new(Ty::Recursive(Box::new(Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::BoundVar(1)),
+            Box::new(Ty::BoundVar(0)),
+        ))))),
+        Box::new(Ty::BoundVar(0)),
+    ))));
+
+    let decompiled = decompile(&ty, Polarity::Pos);
+    let mut auto = Automaton::new();
+    let expected = build(&mut auto, &ty, Polarity::Pos);
+    let actual = build(&mut auto, &decompiled, Polarity::Pos);
+    assert!(equivalent(&auto, expected, actual, Polarity::Pos));
+}
+
+fn equivalent(nfa: &Automaton<Constructor>, a: StateId, b: StateId, pol: Polarity) -> bool {
+    let mut dfa = Automaton::new();
+    let ids: Vec<_> = dfa.reduce(nfa, vec![(a, pol), (b, pol)]).collect();
+    dfa.subsume(ids[0], ids[1], pol) && dfa.subsume(ids[1], ids[0], pol)
+}
+
+proptest! {
+    #![proptest_config(Config {
+        cases: 1024,
+        timeout: 10000,
+        ..Config::default()
+    })]
+
+    #[test]
+    fn decompile_pos(ty in arb_polar_ty(Polarity::Pos)) {
+        let decompiled = decompile(&ty, Polarity::Pos);
+
+        let mut auto = Automaton::new();
+        let expected = build(&mut auto, &ty, Polarity::Pos);
+        let actual = build(&mut auto, &decompiled, Polarity::Pos);
+        prop_assert!(equivalent(&auto, expected, actual, Polarity::Pos));
+    }
+
+    #[test]
+    fn decompile_neg(ty in arb_polar_ty(Polarity::Neg)) {
+        let decompiled = decompile(&ty, Polarity::Neg);
+
+        let mut auto = Automaton::new();
+        let expected = build(&mut auto, &ty, Polarity::Neg);
+        let actual = build(&mut auto, &decompiled, Polarity::Neg);
+        prop_assert!(equivalent(&auto, expected, actual, Polarity::Neg));
+    }
+}
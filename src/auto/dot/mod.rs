@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Write};
+
+use crate::auto::{Automaton, StateId};
+use crate::{Constructor, Label, Polarity};
+
+/// Displays the states of an automaton in the Graphviz DOT language, created by
+/// [`Automaton::to_dot`].
+pub struct Dot<'a, C: Constructor, F> {
+    auto: &'a Automaton<C>,
+    roots: Vec<(StateId, Polarity)>,
+    fmt_constructor: F,
+}
+
+impl<C: Constructor> Automaton<C> {
+    /// Displays the states reachable from `roots` as a Graphviz graph, using `fmt_constructor`
+    /// to print constructors.
+    ///
+    /// Positive states are drawn in blue and negative states in red, with the roots outlined
+    /// twice. Type parameters are drawn as edges labelled by their [`Debug`] representation, and
+    /// flow edges are drawn dashed.
+    pub fn to_dot<I, F>(&self, roots: I, fmt_constructor: F) -> Dot<'_, C, F>
+    where
+        I: IntoIterator<Item = (StateId, Polarity)>,
+        F: Fn(&C, &mut fmt::Formatter) -> fmt::Result,
+        C::Label: Debug,
+    {
+        Dot {
+            auto: self,
+            roots: roots.into_iter().collect(),
+            fmt_constructor,
+        }
+    }
+}
+
+impl<'a, C, F> fmt::Display for Dot<'a, C, F>
+where
+    C: Constructor,
+    C::Label: Debug,
+    F: Fn(&C, &mut fmt::Formatter) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Find the reachable states in breadth-first order, along with their polarity.
+        let mut pols: HashMap<StateId, Polarity> = HashMap::new();
+        let mut order = Vec::new();
+        for &(id, pol) in &self.roots {
+            if pols.insert(id, pol).is_none() {
+                order.push(id);
+            }
+        }
+        let mut idx = 0;
+        while let Some(&id) = order.get(idx) {
+            let pol = pols[&id];
+            for con in self.auto[id].cons.iter() {
+                con.visit_params(|label, set| {
+                    for child in set {
+                        if pols.insert(child, pol * label.polarity()).is_none() {
+                            order.push(child);
+                        }
+                    }
+                });
+            }
+            idx += 1;
+        }
+
+        writeln!(f, "digraph {{")?;
+        for &id in &order {
+            let (color, sep, empty) = match pols[&id] {
+                Polarity::Pos => ("lightblue", " ⊔ ", "⊥"),
+                Polarity::Neg => ("lightpink", " ⊓ ", "⊤"),
+            };
+
+            let mut label = format!("{}: ", id.as_u32());
+            let mut cons = self.auto[id].cons.iter().peekable();
+            if cons.peek().is_none() {
+                label.push_str(empty);
+            }
+            for (idx, con) in cons.enumerate() {
+                if idx != 0 {
+                    label.push_str(sep);
+                }
+                write!(label, "{}", FmtConstructor(con, &self.fmt_constructor))?;
+            }
+
+            write!(
+                f,
+                "    {} [label=\"{}\", style=filled, fillcolor={}",
+                id.as_u32(),
+                escape(&label),
+                color
+            )?;
+            if self.roots.iter().any(|&(root, _)| root == id) {
+                f.write_str(", peripheries=2")?;
+            }
+            writeln!(f, "];")?;
+        }
+
+        for &id in &order {
+            for con in self.auto[id].cons.iter() {
+                let mut result = Ok(());
+                con.visit_params(|label, set| {
+                    for child in set {
+                        result = result.and_then(|()| {
+                            writeln!(
+                                f,
+                                "    {} -> {} [label=\"{}\"];",
+                                id.as_u32(),
+                                child.as_u32(),
+                                escape(&format!("{:?}", label))
+                            )
+                        });
+                    }
+                });
+                result?;
+            }
+        }
+
+        // Flow edges are symmetric, so each is drawn once from its negative state.
+        for &id in &order {
+            if pols[&id] == Polarity::Neg {
+                let mut flow: Vec<_> = self.auto[id]
+                    .flow
+                    .iter()
+                    .filter(|pos| pols.contains_key(pos))
+                    .collect();
+                flow.sort();
+                for pos in flow {
+                    writeln!(
+                        f,
+                        "    {} -> {} [style=dashed, arrowhead=none];",
+                        id.as_u32(),
+                        pos.as_u32()
+                    )?;
+                }
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+struct FmtConstructor<'a, C, F>(&'a C, &'a F);
+
+impl<'a, C, F> fmt::Display for FmtConstructor<'a, C, F>
+where
+    F: Fn(&C, &mut fmt::Formatter) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (self.1)(self.0, f)
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
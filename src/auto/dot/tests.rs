@@ -0,0 +1,59 @@
+use std::fmt;
+use std::iter::once;
+
+use crate::auto::{Automaton, StateId};
+use crate::polar::Ty;
+use crate::tests::{Constructed, Constructor};
+use crate::Polarity;
+
+fn fmt_constructor(con: &Constructor, f: &mut fmt::Formatter) -> fmt::Result {
+    match con {
+        Constructor::Bool => f.write_str("bool"),
+        Constructor::Fun(..) => f.write_str("fun"),
+        Constructor::Record(_) => f.write_str("record"),
+        Constructor::Variant(_) => f.write_str("variant"),
+    }
+}
+
+fn build(ty: &str, pol: Polarity) -> (Automaton<Constructor>, StateId) {
+    let ty: Ty<Constructed, char> = Ty::parse(ty).unwrap();
+    let mut nfa = Automaton::new();
+    let id = nfa.builder().build_polar(pol, &ty);
+
+    let mut dfa = Automaton::new();
+    let id = dfa.reduce(&nfa, once((id, pol))).next().unwrap();
+    (dfa, id)
+}
+
+#[test]
+fn dot_fun() {
+    let (auto, id) = build("'a -> 'a", Polarity::Pos);
+    assert_eq!(
+        auto.to_dot(once((id, Polarity::Pos)), fmt_constructor)
+            .to_string(),
+        "digraph {
+    0 [label=\"0: fun\", style=filled, fillcolor=lightblue, peripheries=2];
+    1 [label=\"1: ⊤\", style=filled, fillcolor=lightpink];
+    2 [label=\"2: ⊥\", style=filled, fillcolor=lightblue];
+    0 -> 1 [label=\"Domain\"];
+    0 -> 2 [label=\"Range\"];
+    1 -> 2 [style=dashed, arrowhead=none];
+}
+"
+    );
+}
+
+#[test]
+fn dot_add() {
+    let (auto, id) = build("bool & {x: bool}", Polarity::Neg);
+    assert_eq!(
+        auto.to_dot(once((id, Polarity::Neg)), fmt_constructor)
+            .to_string(),
+        "digraph {
+    0 [label=\"0: bool ⊓ record\", style=filled, fillcolor=lightpink, peripheries=2];
+    1 [label=\"1: bool\", style=filled, fillcolor=lightpink];
+    0 -> 1 [label=\"Label(\\\"x\\\")\"];
+}
+"
+    );
+}
@@ -1,9 +1,10 @@
 use std::hash::BuildHasherDefault;
-use std::iter::FromIterator;
+use std::iter::{Copied, FromIterator};
+use std::mem::take;
 
 use im::{hashset, HashSet};
-use seahash::SeaHasher;
 use once_cell::sync::Lazy;
+use seahash::SeaHasher;
 
 use crate::auto::{Automaton, StateId};
 use crate::{Constructor, Polarity};
@@ -34,8 +35,8 @@ impl Pair {
 }
 
 impl FlowSet {
-    pub fn iter(&self) -> hashset::ConsumingIter<StateId> {
-        self.set.clone().into_iter()
+    pub fn iter(&self) -> Copied<hashset::Iter<'_, StateId>> {
+        self.set.iter().copied()
     }
 
     pub(in crate::auto) fn from_iter<I>(iter: I) -> Self
@@ -51,15 +52,31 @@ impl FlowSet {
         FlowSet::from_iter(self.set.into_iter().map(|id| id.shift(offset)))
     }
 
+    pub(in crate::auto) fn filter_map<F>(self, mapper: F) -> Self
+    where
+        F: FnMut(StateId) -> Option<StateId>,
+    {
+        FlowSet::from_iter(self.set.into_iter().filter_map(mapper))
+    }
+
     pub(in crate::auto) fn union(&mut self, other: &Self) {
         self.set.extend(other.iter());
     }
 }
 
+impl<'a> IntoIterator for &'a FlowSet {
+    type IntoIter = Copied<hashset::Iter<'a, StateId>>;
+    type Item = StateId;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl Default for FlowSet {
     fn default() -> Self {
         static EMPTY: Lazy<FlowSet> = Lazy::new(|| FlowSet {
-            set: HashSet::default()
+            set: HashSet::default(),
         });
 
         EMPTY.clone()
@@ -68,9 +85,7 @@ impl Default for FlowSet {
 
 impl<C: Constructor> Automaton<C> {
     pub(crate) fn add_flow(&mut self, pair: Pair) {
-        #[cfg(debug_assertions)]
         debug_assert_eq!(self[pair.pos].pol, Polarity::Pos);
-        #[cfg(debug_assertions)]
         debug_assert_eq!(self[pair.neg].pol, Polarity::Neg);
 
         let had_p = self[pair.pos].flow.set.insert(pair.neg).is_some();
@@ -79,9 +94,7 @@ impl<C: Constructor> Automaton<C> {
     }
 
     pub(crate) fn remove_flow(&mut self, pair: Pair) {
-        #[cfg(debug_assertions)]
         debug_assert_eq!(self[pair.pos].pol, Polarity::Pos);
-        #[cfg(debug_assertions)]
         debug_assert_eq!(self[pair.neg].pol, Polarity::Neg);
 
         let had_p = self[pair.pos].flow.set.remove(&pair.neg).is_some();
@@ -90,23 +103,25 @@ impl<C: Constructor> Automaton<C> {
     }
 
     pub(crate) fn has_flow(&self, pair: Pair) -> bool {
-        #[cfg(debug_assertions)]
         debug_assert_eq!(self[pair.pos].pol, Polarity::Pos);
-        #[cfg(debug_assertions)]
         debug_assert_eq!(self[pair.neg].pol, Polarity::Neg);
 
         self[pair.neg].flow.set.contains(&pair.pos)
     }
 
     pub(crate) fn merge_flow(&mut self, pol: Polarity, a: StateId, source: StateId) {
-        #[cfg(debug_assertions)]
         debug_assert_eq!(self[a].pol, pol);
-        #[cfg(debug_assertions)]
         debug_assert_eq!(self[source].pol, pol);
 
-        for b in self[source].flow.iter() {
+        debug_assert_ne!(a, source);
+
+        // Adding flow from `a` does not change the flow of `source`, so it can be taken out of
+        // the automaton while its edges are added.
+        let flow = take(&mut self[source].flow);
+        for b in &flow {
             self.add_flow(Pair::from_pol(pol, a, b));
         }
+        self[source].flow = flow;
     }
 
     #[cfg(debug_assertions)]
@@ -0,0 +1,323 @@
+//! A compact, versioned binary format for the exported types of a module, so that it can be
+//! checked separately from the modules which use it.
+//!
+//! An interface file holds a reduced fragment of an automaton, written by
+//! [`write_interface`](Automaton::write_interface). It starts with a header of the magic bytes
+//! `MLSB`, the format version and the number of states and roots, followed by a table of
+//! states with their polarity and constructors, and a table of flow edges. All integers are
+//! encoded as unsigned LEB128.
+
+#[cfg(test)]
+mod tests;
+
+use std::borrow::Cow;
+use std::error::Error as StdError;
+use std::fmt;
+use std::str;
+
+use crate::auto::{flow, Automaton, State, StateId, StateRange, StateSet};
+use crate::{Constructor, Polarity};
+
+const MAGIC: &[u8; 4] = b"MLSB";
+
+/// The version of the format written by this crate.
+pub const VERSION: u32 = 1;
+
+/// Encoding of the constructors in an interface file.
+pub trait Encode: Constructor {
+    fn encode(&self, writer: &mut Writer);
+
+    /// Decodes a constructor written by [`encode`](Encode::encode). The reader is limited to
+    /// the bytes written for this constructor, and must be fully consumed.
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, Error>;
+}
+
+/// Writes the payload of a constructor.
+#[derive(Debug, Default)]
+pub struct Writer {
+    buf: Vec<u8>,
+}
+
+/// Reads the payload of a constructor.
+#[derive(Debug)]
+pub struct Reader<'a> {
+    input: &'a [u8],
+    // The number of states in the fragment being read.
+    len: u32,
+}
+
+/// An error from loading an interface file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The input does not start with the magic bytes of an interface file.
+    Magic,
+    /// The input was written with an unsupported version of the format.
+    Version(u32),
+    /// The input ended unexpectedly.
+    Eof,
+    /// The input is not a well-formed automaton.
+    Invalid(String),
+}
+
+impl<C: Encode> Automaton<C> {
+    /// Writes the states reachable from `roots` as an interface file.
+    ///
+    /// The states are [reduced](Automaton::reduce), so the file holds the smallest deterministic
+    /// fragment of this automaton needed to represent the roots.
+    pub fn write_interface<I>(&self, roots: I) -> Vec<u8>
+    where
+        I: IntoIterator<Item = (StateId, Polarity)>,
+    {
+        let roots: Vec<_> = roots.into_iter().collect();
+        let mut dfa = Automaton::new();
+        dfa.reduce(self, roots.iter().copied());
+
+        let mut writer = Writer::default();
+        writer.buf.extend_from_slice(MAGIC);
+        writer.write_u32(VERSION);
+        writer.write_u32(dfa.states.len() as u32);
+        writer.write_u32(roots.len() as u32);
+
+        for state in &dfa.states {
+            writer.write_polarity(state.pol);
+            writer.write_u32(state.cons.iter().count() as u32);
+            for con in state.cons.iter() {
+                let mut payload = Writer::default();
+                con.encode(&mut payload);
+                writer.write_bytes(&payload.buf);
+            }
+        }
+
+        // Each flow edge is written once, from its negative state.
+        let mut flow = Vec::new();
+        for (id, state) in dfa.enumerate() {
+            if state.pol == Polarity::Neg {
+                let mut pos: Vec<_> = state.flow.iter().collect();
+                pos.sort();
+                flow.extend(pos.into_iter().map(|pos| (id, pos)));
+            }
+        }
+        writer.write_u32(flow.len() as u32);
+        for (neg, pos) in flow {
+            writer.write_u32(neg.0);
+            writer.write_u32(pos.0);
+        }
+
+        writer.buf
+    }
+
+    /// Loads an interface file written by [`write_interface`](Automaton::write_interface),
+    /// appending its states to this automaton like [`add_from`](Automaton::add_from). Returns
+    /// the states corresponding to the roots the file was written with.
+    pub fn read_interface(&mut self, input: &[u8]) -> Result<StateRange, Error> {
+        if !input.starts_with(MAGIC) {
+            return Err(Error::Magic);
+        }
+        let mut reader = Reader {
+            input: &input[MAGIC.len()..],
+            len: 0,
+        };
+        let version = reader.read_u32()?;
+        if version != VERSION {
+            return Err(Error::Version(version));
+        }
+        reader.len = reader.read_u32()?;
+        let roots = reader.read_u32()?;
+        if roots > reader.len {
+            return Err(Error::Invalid("more roots than states".to_owned()));
+        }
+
+        let mut fragment = Automaton::new();
+        let mut range = None;
+        for idx in 0..reader.len {
+            if idx == roots {
+                range = Some(fragment.range_from(StateId(0)));
+            }
+
+            let pol = reader.read_polarity()?;
+            let mut state = State::new(pol);
+            for _ in 0..reader.read_u32()? {
+                let mut payload = reader.read_payload()?;
+                let con = C::decode(&mut payload)?;
+                if !payload.input.is_empty() {
+                    return Err(Error::Invalid(
+                        "unexpected data after constructor".to_owned(),
+                    ));
+                }
+                if state.cons.get(con.component()).is_some() {
+                    return Err(Error::Invalid(
+                        "found more than one constructor with the same component".to_owned(),
+                    ));
+                }
+                state.cons.add(pol, Cow::Owned(con));
+            }
+            fragment.add(state);
+        }
+        let range = range.unwrap_or_else(|| fragment.range_from(StateId(0)));
+
+        for _ in 0..reader.read_u32()? {
+            let neg = reader.read_state()?;
+            let pos = reader.read_state()?;
+            if fragment[neg].pol != Polarity::Neg || fragment[pos].pol != Polarity::Pos {
+                return Err(Error::Invalid(format!(
+                    "flow edge from state {} to {} does not go from a negative to a positive state",
+                    neg.0, pos.0
+                )));
+            }
+            fragment.add_flow(flow::Pair { neg, pos });
+        }
+        if !reader.input.is_empty() {
+            return Err(Error::Invalid(
+                "unexpected data after flow table".to_owned(),
+            ));
+        }
+
+        fragment
+            .validate()
+            .map_err(|err| Error::Invalid(err.to_string()))?;
+
+        let offset = self.add_from(&fragment);
+        Ok(range.shift(offset))
+    }
+}
+
+impl Writer {
+    pub fn write_u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    pub fn write_u32(&mut self, mut value: u32) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.buf.push(byte);
+                return;
+            }
+            self.buf.push(byte | 0x80);
+        }
+    }
+
+    /// Writes a length-prefixed byte string.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_u32(bytes.len() as u32);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    pub fn write_str(&mut self, s: &str) {
+        self.write_bytes(s.as_bytes());
+    }
+
+    /// Writes a reference to a set of states, which must be in the fragment being written.
+    pub fn write_state_set(&mut self, set: &StateSet) {
+        self.write_u32(set.iter().count() as u32);
+        for id in set {
+            self.write_u32(id.0);
+        }
+    }
+
+    fn write_polarity(&mut self, pol: Polarity) {
+        self.write_u8(match pol {
+            Polarity::Pos => 0,
+            Polarity::Neg => 1,
+        });
+    }
+}
+
+impl<'a> Reader<'a> {
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        match self.input.split_first() {
+            Some((&byte, rest)) => {
+                self.input = rest;
+                Ok(byte)
+            }
+            None => Err(Error::Eof),
+        }
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, Error> {
+        let mut value = 0u32;
+        for shift in (0..32).step_by(7) {
+            let byte = self.read_u8()?;
+            let bits = u32::from(byte & 0x7f);
+            if bits << shift >> shift != bits {
+                break;
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::Invalid("integer is too large".to_owned()))
+    }
+
+    /// Reads a length-prefixed byte string.
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.read_u32()? as usize;
+        if len > self.input.len() {
+            return Err(Error::Eof);
+        }
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    pub fn read_str(&mut self) -> Result<&'a str, Error> {
+        str::from_utf8(self.read_bytes()?)
+            .map_err(|_| Error::Invalid("string is not valid UTF-8".to_owned()))
+    }
+
+    /// Reads a reference to a non-empty set of states in the fragment being read.
+    pub fn read_state_set(&mut self) -> Result<StateSet, Error> {
+        let len = self.read_u32()?;
+        if len == 0 {
+            return Err(Error::Invalid("empty set of states".to_owned()));
+        }
+        let mut set = StateSet::new(self.read_state()?);
+        for _ in 1..len {
+            set.insert(self.read_state()?);
+        }
+        Ok(set)
+    }
+
+    fn read_state(&mut self) -> Result<StateId, Error> {
+        let id = self.read_u32()?;
+        if id < self.len {
+            Ok(StateId(id))
+        } else {
+            Err(Error::Invalid(format!("state {} does not exist", id)))
+        }
+    }
+
+    fn read_polarity(&mut self) -> Result<Polarity, Error> {
+        match self.read_u8()? {
+            0 => Ok(Polarity::Pos),
+            1 => Ok(Polarity::Neg),
+            _ => Err(Error::Invalid("invalid polarity".to_owned())),
+        }
+    }
+
+    fn read_payload(&mut self) -> Result<Reader<'a>, Error> {
+        Ok(Reader {
+            input: self.read_bytes()?,
+            len: self.len,
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Magic => f.write_str("not an interface file"),
+            Error::Version(version) => write!(
+                f,
+                "unsupported interface file version {} (expected {})",
+                version, VERSION
+            ),
+            Error::Eof => f.write_str("unexpected end of interface file"),
+            Error::Invalid(message) => write!(f, "invalid interface file: {}", message),
+        }
+    }
+}
+
+impl StdError for Error {}
@@ -0,0 +1,172 @@
+use std::iter::once;
+
+use proptest::test_runner::Config;
+use proptest::{prop_assert, proptest};
+
+use crate::auto::interface::{Error, VERSION};
+use crate::auto::{Automaton, StateId};
+use crate::polar::Ty;
+use crate::tests::{arb_polar_ty, Constructed, Constructor};
+use crate::Polarity;
+
+fn build(auto: &mut Automaton<Constructor>, ty: &str, pol: Polarity) -> StateId {
+    let ty: Ty<Constructed, char> = Ty::parse(ty).unwrap();
+    auto.builder().build_polar(pol, &ty)
+}
+
+fn write(ty: &str, pol: Polarity) -> Vec<u8> {
+    let mut auto = Automaton::new();
+    let id = build(&mut auto, ty, pol);
+    auto.write_interface(once((id, pol)))
+}
+
+fn read(bytes: &[u8]) -> Result<Automaton<Constructor>, Error> {
+    let mut auto = Automaton::new();
+    auto.read_interface(bytes)?;
+    Ok(auto)
+}
+
+fn check_roundtrip(ty: &Ty<Constructed, char>, pol: Polarity) -> bool {
+    let mut nfa = Automaton::new();
+    let id = nfa.builder().build_polar(pol, ty);
+    let bytes = nfa.write_interface(once((id, pol)));
+
+    let mut dfa = Automaton::new();
+    let expected = dfa.reduce(&nfa, once((id, pol))).next().unwrap();
+    let expected: Ty<Constructed<usize>, usize> = dfa.to_polar_ty(expected, pol);
+
+    let mut auto = Automaton::new();
+    let actual = auto.read_interface(&bytes).unwrap().next().unwrap();
+    auto.to_polar_ty(actual, pol) == expected
+}
+
+#[test]
+fn roundtrip() {
+    let ty = Ty::parse("rec a. {x: 'a -> 'a, y: a} | [A: bool]").unwrap();
+    assert!(check_roundtrip(&ty, Polarity::Pos));
+    let ty = Ty::parse("{x: 'a, y: bool -> 'a} & 'b -> 'b").unwrap();
+    assert!(check_roundtrip(&ty, Polarity::Neg));
+}
+
+#[test]
+fn format() {
+    assert_eq!(
+        write("'a -> 'a", Polarity::Pos),
+        [
+            b'M', b'L', b'S', b'B', // magic
+            1, 3, 1, // version, states, roots
+            0, 1, 5, 1, 1, 1, 1, 2, // state 0: Pos, [Fun([1], [2])]
+            1, 0, // state 1: Neg, []
+            0, 0, // state 2: Pos, []
+            1, 1, 2, // flow: [(1, 2)]
+        ]
+    );
+}
+
+#[test]
+fn append() {
+    let bytes = write("'a -> 'a", Polarity::Pos);
+
+    let mut auto = Automaton::new();
+    let existing = build(&mut auto, "bool -> bool", Polarity::Neg);
+    let len = auto.states.len() as u32;
+    let fst = auto.read_interface(&bytes).unwrap().next().unwrap();
+    let snd = auto.read_interface(&bytes).unwrap().next().unwrap();
+    assert_eq!(fst, StateId(len));
+    assert_eq!(snd, StateId(len + 3));
+
+    // The loaded states are independent of each other and the existing states.
+    auto.biunify(fst, existing).unwrap();
+    let ty: Ty<Constructed<usize>, usize> = auto.to_polar_ty(snd, Polarity::Pos);
+    assert_eq!(
+        ty,
+        Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::UnboundVar(0)),
+            Box::new(Ty::UnboundVar(0))
+        ))
+    );
+}
+
+#[test]
+fn roots() {
+    let mut nfa = Automaton::new();
+    let bool = build(&mut nfa, "bool", Polarity::Pos);
+    let fun = build(&mut nfa, "bool -> bool", Polarity::Neg);
+    let bytes = nfa.write_interface(vec![
+        (fun, Polarity::Neg),
+        (bool, Polarity::Pos),
+        (fun, Polarity::Neg),
+    ]);
+
+    let mut auto = Automaton::new();
+    build(&mut auto, "bool", Polarity::Pos);
+    let ids: Vec<_> = auto.read_interface(&bytes).unwrap().collect();
+    assert_eq!(ids.len(), 3);
+    let tys: Vec<Ty<Constructed<usize>, usize>> = ids
+        .iter()
+        .zip(&[Polarity::Neg, Polarity::Pos, Polarity::Neg])
+        .map(|(&id, &pol)| auto.to_polar_ty(id, pol))
+        .collect();
+    assert_eq!(tys[0], tys[2]);
+    assert_eq!(tys[1], Ty::Constructed(Constructed::Bool));
+}
+
+#[test]
+fn invalid() {
+    let bytes = write("rec a. {x: 'a -> 'a, y: a} | [A: bool]", Polarity::Pos);
+
+    assert_eq!(read(b"MLS").unwrap_err(), Error::Magic);
+    let mut future = bytes.clone();
+    future[4] = VERSION as u8 + 1;
+    assert_eq!(read(&future).unwrap_err(), Error::Version(VERSION + 1));
+    for len in 4..bytes.len() {
+        assert_eq!(read(&bytes[..len]).unwrap_err(), Error::Eof);
+    }
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(read(&trailing).is_err());
+
+    assert_eq!(
+        read(&[b'M', b'L', b'S', b'B', 1, 1, 1, 0, 1, 5, 1, 1, 1, 1, 0])
+            .unwrap_err()
+            .to_string(),
+        "invalid interface file: state 1 does not exist"
+    );
+    assert_eq!(
+        read(&[b'M', b'L', b'S', b'B', 1, 1, 1, 0, 1, 5, 1, 1, 0, 1, 0, 0])
+            .unwrap_err()
+            .to_string(),
+        "invalid interface file: state 0 has a type parameter 0 of the wrong polarity"
+    );
+    assert_eq!(
+        read(&[b'M', b'L', b'S', b'B', 1, 2, 1, 0, 0, 0, 0, 1, 0, 1])
+            .unwrap_err()
+            .to_string(),
+        "invalid interface file: flow edge from state 0 to 1 does not go from a negative to a \
+         positive state"
+    );
+    assert_eq!(
+        read(&[b'M', b'L', b'S', b'B', 1, 1, 1, 0, 2, 1, 0, 1, 0, 0])
+            .unwrap_err()
+            .to_string(),
+        "invalid interface file: found more than one constructor with the same component"
+    );
+}
+
+proptest! {
+    #![proptest_config(Config {
+        cases: 1024,
+        timeout: 10000,
+        ..Config::default()
+    })]
+
+    #[test]
+    fn roundtrip_pos(ty in arb_polar_ty(Polarity::Pos)) {
+        prop_assert!(check_roundtrip(&ty, Polarity::Pos));
+    }
+
+    #[test]
+    fn roundtrip_neg(ty in arb_polar_ty(Polarity::Neg)) {
+        prop_assert!(check_roundtrip(&ty, Polarity::Neg));
+    }
+}
@@ -0,0 +1,315 @@
+#[cfg(test)]
+mod tests;
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::auto::{Automaton, FlowSet, State, StateId, StateRange, StateSet};
+use crate::{Constructor, Label, Polarity};
+
+impl<C: Constructor> Automaton<C> {
+    /// Copies the states of a reduced automaton reachable from `dfa_ids` into this automaton,
+    /// merging states which represent the same type.
+    ///
+    /// Like [`reduce`](Automaton::reduce), the returned range starts with one state for each
+    /// of `dfa_ids`, in order.
+    pub fn minimize<I>(&mut self, dfa: &Self, dfa_ids: I) -> StateRange
+    where
+        I: IntoIterator<Item = (StateId, Polarity)>,
+    {
+        let roots: Vec<_> = dfa_ids.into_iter().collect();
+        let partition = Partition::new(dfa, &roots);
+
+        // Allocate a state for each root, followed by the remaining blocks.
+        let start = self.next();
+        let mut ids: Vec<Option<StateId>> = vec![None; partition.len];
+        let mut copies: Vec<Vec<StateId>> = vec![Vec::new(); partition.len];
+        let mut reprs = Vec::with_capacity(partition.len + roots.len());
+        for (idx, &(root, _)) in roots.iter().enumerate() {
+            let id = start.shift(idx as u32);
+            let block = partition.block(root);
+            ids[block].get_or_insert(id);
+            copies[block].push(id);
+            reprs.push(partition.index[&root]);
+        }
+        for (idx, &(block, _)) in partition.states.iter().enumerate() {
+            if ids[block].is_none() {
+                let id = start.shift(reprs.len() as u32);
+                ids[block] = Some(id);
+                copies[block].push(id);
+                reprs.push(idx);
+            }
+        }
+
+        let mut range = None;
+        for (idx, repr) in reprs.into_iter().enumerate() {
+            if idx == roots.len() {
+                range = Some(self.range_from(start));
+            }
+
+            let pol = partition.states[repr].1;
+            let nfa_id = partition.ids[repr];
+
+            let mut state = State::new(pol);
+            for con in dfa[nfa_id].cons.iter() {
+                let con = con.clone().map(|_, set| {
+                    StateSet::new(ids[partition.block(set.unwrap_reduced())].unwrap())
+                });
+                state.cons.add(pol, Cow::Owned(con));
+            }
+            // Flow edges are added to every copy of a block to keep the flow relation symmetric.
+            state.flow = FlowSet::from_iter(
+                dfa[nfa_id]
+                    .flow
+                    .iter()
+                    .filter(|id| partition.index.contains_key(id))
+                    .flat_map(|id| copies[partition.block(id)].iter().copied()),
+            );
+            self.add(state);
+        }
+
+        #[cfg(debug_assertions)]
+        debug_assert!(self.check_flow());
+
+        range.unwrap_or_else(|| self.range_from(start))
+    }
+}
+
+struct Partition {
+    // The states of the dfa reachable from the roots.
+    ids: Vec<StateId>,
+    // Maps reachable states to their index in `ids`.
+    index: HashMap<StateId, usize>,
+    // The current block and polarity of each reachable state.
+    states: Vec<(usize, Polarity)>,
+    // The number of blocks.
+    len: usize,
+}
+
+impl Partition {
+    fn new<C: Constructor>(dfa: &Automaton<C>, roots: &[(StateId, Polarity)]) -> Self {
+        let mut partition = Partition {
+            ids: Vec::new(),
+            index: HashMap::new(),
+            states: Vec::new(),
+            len: 0,
+        };
+
+        // The type parameters of each reachable state, in order.
+        let mut params: Vec<Vec<StateId>> = Vec::new();
+        let mut stack: Vec<_> = roots.iter().rev().copied().collect();
+        while let Some((id, pol)) = stack.pop() {
+            debug_assert_eq!(dfa[id].pol, pol);
+
+            if !partition.index.contains_key(&id) {
+                partition.index.insert(id, partition.ids.len());
+                partition.ids.push(id);
+                partition.states.push((0, pol));
+                let mut ids = Vec::new();
+                for con in dfa[id].cons.iter() {
+                    con.visit_params(|label, set| {
+                        ids.push(set.unwrap_reduced());
+                        stack.push((set.unwrap_reduced(), pol * label.polarity()));
+                    });
+                }
+                params.push(ids);
+            }
+        }
+        let params: Vec<Vec<usize>> = params
+            .into_iter()
+            .map(|ids| ids.iter().map(|id| partition.index[id]).collect())
+            .collect();
+
+        let mut blocks = partition.initial(dfa);
+        blocks.refine(&params);
+
+        partition.len = blocks.start.len();
+        for (idx, state) in partition.states.iter_mut().enumerate() {
+            state.0 = blocks.block[idx];
+        }
+        partition
+    }
+
+    fn block(&self, id: StateId) -> usize {
+        self.states[self.index[&id]].0
+    }
+
+    // Partitions the states by polarity, flow edges and constructors, ignoring their type
+    // parameters.
+    fn initial<C: Constructor>(&self, dfa: &Automaton<C>) -> Blocks {
+        // Constructors can only be compared pairwise, so states are first grouped by the other
+        // properties, and then compared with one representative of each block in their group.
+        let mut groups: BTreeMap<_, Vec<usize>> = BTreeMap::new();
+        let mut reprs: Vec<usize> = Vec::new();
+        let mut block = Vec::with_capacity(self.ids.len());
+        for (idx, &id) in self.ids.iter().enumerate() {
+            // Merging states with different flow edges would introduce new flow between them.
+            // Comparing the blocks of their flow partners is not enough: merging `a → a` with
+            // `b → b` would identify the variables `a` and `b`, giving a less general type.
+            let mut flow: Vec<_> = dfa[id]
+                .flow
+                .iter()
+                .filter(|id| self.index.contains_key(id))
+                .collect();
+            flow.sort();
+            let components: Vec<_> = dfa[id].cons.iter().map(|con| con.component()).collect();
+
+            let group = groups
+                .entry((self.states[idx].1 == Polarity::Pos, flow, components))
+                .or_default();
+            let existing = group
+                .iter()
+                .copied()
+                .find(|&b| same_constructors(dfa, id, self.ids[reprs[b]]));
+            block.push(existing.unwrap_or_else(|| {
+                reprs.push(idx);
+                group.push(reprs.len() - 1);
+                reprs.len() - 1
+            }));
+        }
+        Blocks::new(block, reprs.len())
+    }
+}
+
+// Whether two states have equal constructors, regardless of their type parameters.
+fn same_constructors<C: Constructor>(dfa: &Automaton<C>, a: StateId, b: StateId) -> bool {
+    let a = &dfa[a].cons;
+    let b = &dfa[b].cons;
+
+    let len = a.iter().count();
+    len == b.iter().count()
+        && len == a.intersection(b).count()
+        && a.intersection(b)
+            .all(|(l, r)| l.partial_cmp(r) == Some(Ordering::Equal))
+}
+
+// A partition of the reachable states, refined using Hopcroft's algorithm. The n-th type
+// parameter of a state is treated as its transition on the symbol n: states in the same initial
+// block have equal constructors, so their parameters have the same labels in the same order.
+struct Blocks {
+    // The states, ordered so that each block is a contiguous range.
+    elems: Vec<usize>,
+    // The position of each state in `elems`.
+    pos: Vec<usize>,
+    // The block of each state.
+    block: Vec<usize>,
+    // The range of `elems` covered by each block.
+    start: Vec<usize>,
+    end: Vec<usize>,
+    // The number of states at the start of each block marked by the current splitter.
+    marked: Vec<usize>,
+}
+
+impl Blocks {
+    fn new(block: Vec<usize>, len: usize) -> Self {
+        let mut elems: Vec<usize> = (0..block.len()).collect();
+        elems.sort_by_key(|&idx| block[idx]);
+        let mut pos = vec![0; elems.len()];
+        let mut start = vec![0; len];
+        let mut end = vec![0; len];
+        for (p, &idx) in elems.iter().enumerate() {
+            pos[idx] = p;
+            if p == 0 || block[elems[p - 1]] != block[idx] {
+                start[block[idx]] = p;
+            }
+            end[block[idx]] = p + 1;
+        }
+        Blocks {
+            elems,
+            pos,
+            block,
+            start,
+            end,
+            marked: vec![0; len],
+        }
+    }
+
+    fn refine(&mut self, params: &[Vec<usize>]) {
+        // The states with a transition on each symbol into each state.
+        let symbols = params.iter().map(Vec::len).max().unwrap_or(0);
+        let mut preds: Vec<Vec<(usize, usize)>> = vec![Vec::new(); params.len()];
+        for (idx, targets) in params.iter().enumerate() {
+            for (symbol, &target) in targets.iter().enumerate() {
+                preds[target].push((symbol, idx));
+            }
+        }
+
+        let mut pending: HashSet<(usize, usize)> = HashSet::new();
+        let mut work = Vec::new();
+        for block in 0..self.start.len() {
+            for symbol in 0..symbols {
+                pending.insert((block, symbol));
+                work.push((block, symbol));
+            }
+        }
+
+        let mut splitter = Vec::new();
+        let mut touched = Vec::new();
+        while let Some((block, symbol)) = work.pop() {
+            pending.remove(&(block, symbol));
+
+            splitter.clear();
+            splitter.extend_from_slice(&self.elems[self.start[block]..self.end[block]]);
+            for &target in &splitter {
+                for &(sym, pred) in &preds[target] {
+                    if sym == symbol {
+                        let b = self.block[pred];
+                        if self.marked[b] == 0 {
+                            touched.push(b);
+                        }
+                        self.mark(pred);
+                    }
+                }
+            }
+
+            for b in touched.drain(..) {
+                let marked = std::mem::replace(&mut self.marked[b], 0);
+                if marked == self.end[b] - self.start[b] {
+                    continue;
+                }
+
+                // Split the marked states into a new block, and make sure both halves are used
+                // as splitters if the original block was still going to be.
+                let new = self.start.len();
+                self.start.push(self.start[b]);
+                self.end.push(self.start[b] + marked);
+                self.marked.push(0);
+                self.start[b] += marked;
+                for p in self.start[new]..self.end[new] {
+                    self.block[self.elems[p]] = new;
+                }
+
+                let smaller = if marked <= self.end[b] - self.start[b] {
+                    new
+                } else {
+                    b
+                };
+                for sym in 0..symbols {
+                    let added = if pending.contains(&(b, sym)) {
+                        new
+                    } else {
+                        smaller
+                    };
+                    if pending.insert((added, sym)) {
+                        work.push((added, sym));
+                    }
+                }
+            }
+        }
+    }
+
+    // Moves a state to the marked range at the start of its block.
+    fn mark(&mut self, idx: usize) {
+        let block = self.block[idx];
+        let p = self.pos[idx];
+        let q = self.start[block] + self.marked[block];
+        if p >= q {
+            let other = self.elems[q];
+            self.elems.swap(p, q);
+            self.pos[idx] = q;
+            self.pos[other] = p;
+            self.marked[block] += 1;
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::iter::once;
+use std::rc::Rc;
+
+use proptest::test_runner::{Config, TestCaseError};
+use proptest::{prop_assert, prop_assert_eq, proptest};
+
+use crate::auto::{Automaton, StateId};
+use crate::polar::Ty;
+use crate::tests::{arb_polar_ty, Constructed, Constructor};
+use crate::Polarity;
+
+fn reduce(ty: &Ty<Constructed, char>, pol: Polarity) -> (Automaton<Constructor>, StateId) {
+    let mut nfa = Automaton::new();
+    let id = nfa.builder().build_polar(pol, ty);
+
+    let mut dfa = Automaton::new();
+    let id = dfa.reduce(&nfa, once((id, pol))).next().unwrap();
+    (dfa, id)
+}
+
+fn minimize(
+    dfa: &Automaton<Constructor>,
+    id: StateId,
+    pol: Polarity,
+) -> (Automaton<Constructor>, StateId) {
+    let mut min = Automaton::new();
+    let id = min.minimize(dfa, once((id, pol))).next().unwrap();
+    (min, id)
+}
+
+fn check_minimize(ty: &Ty<Constructed, char>, pol: Polarity) -> Result<(), TestCaseError> {
+    let (mut dfa, id) = reduce(ty, pol);
+    let (min, min_id) = minimize(&dfa, id, pol);
+    prop_assert!(min.states.len() <= dfa.states.len());
+
+    let (min_again, _) = minimize(&min, min_id, pol);
+    prop_assert_eq!(min_again.states.len(), min.states.len());
+
+    let min_id = min_id.shift(dfa.add_from(&min));
+    prop_assert!(dfa.subsume(id, min_id, pol));
+    prop_assert!(dfa.subsume(min_id, id, pol));
+    Ok(())
+}
+
+#[test]
+fn minimize_unrolled() {
+    // bool → rec a. bool → a
+    let ty = Ty::Constructed(Constructed::Fun(
+        Box::new(Ty::Constructed(Constructed::Bool)),
+        Box::new(Ty::Recursive(Box::new(Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::Constructed(Constructed::Bool)),
+            Box::new(Ty::BoundVar(0)),
+        ))))),
+    ));
+
+    let (dfa, id) = reduce(&ty, Polarity::Pos);
+    assert_eq!(dfa.states.len(), 4);
+    let (min, _) = minimize(&dfa, id, Polarity::Pos);
+    assert_eq!(min.states.len(), 2);
+}
+
+#[test]
+fn minimize_equivalent_roots() {
+    let ty = Ty::Constructed(Constructed::Fun(
+        Box::new(Ty::UnboundVar('a')),
+        Box::new(Ty::UnboundVar('a')),
+    ));
+
+    let (dfa, id) = reduce(&ty, Polarity::Pos);
+    let mut min = Automaton::new();
+    let range: Vec<_> = min
+        .minimize(&dfa, vec![(id, Polarity::Pos), (id, Polarity::Pos)])
+        .collect();
+
+    assert_eq!(range.len(), 2);
+    assert_ne!(range[0], range[1]);
+    assert_eq!(min.states.len(), 4);
+    #[cfg(debug_assertions)]
+    assert!(min.check_flow());
+}
+
+fn record_of_ids(x: char, y: char) -> Ty<Constructed, char> {
+    let id = |v| {
+        Box::new(Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::UnboundVar(v)),
+            Box::new(Ty::UnboundVar(v)),
+        )))
+    };
+    Ty::Constructed(Constructed::Record(
+        vec![(Rc::from("x"), id(x)), (Rc::from("y"), id(y))]
+            .into_iter()
+            .collect(),
+    ))
+}
+
+#[test]
+fn minimize_shared_var() {
+    // {x: a → a, y: a → a}
+    let ty = record_of_ids('a', 'a');
+
+    let (dfa, id) = reduce(&ty, Polarity::Pos);
+    assert_eq!(dfa.states.len(), 7);
+    let (min, _) = minimize(&dfa, id, Polarity::Pos);
+    assert_eq!(min.states.len(), 4);
+    check_minimize(&ty, Polarity::Pos).unwrap();
+}
+
+#[test]
+fn minimize_distinct_vars() {
+    // {x: a → a, y: b → b}
+    let ty = record_of_ids('a', 'b');
+
+    let (dfa, id) = reduce(&ty, Polarity::Pos);
+    let (min, _) = minimize(&dfa, id, Polarity::Pos);
+    assert_eq!(min.states.len(), dfa.states.len());
+    check_minimize(&ty, Polarity::Pos).unwrap();
+}
+
+proptest! {
+    #![proptest_config(Config {
+        cases: 1024,
+        timeout: 10000,
+        ..Config::default()
+    })]
+
+    #[test]
+    fn minimize_pos(ty in arb_polar_ty(Polarity::Pos)) {
+        check_minimize(&ty, Polarity::Pos)?;
+    }
+
+    #[test]
+    fn minimize_neg(ty in arb_polar_ty(Polarity::Neg)) {
+        check_minimize(&ty, Polarity::Neg)?;
+    }
+}
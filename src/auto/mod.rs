@@ -1,14 +1,28 @@
 pub mod flow;
+pub mod interface;
 pub mod state;
 
 pub(crate) mod build;
 
+mod compact;
+mod decompile;
+mod dot;
+mod minimize;
 mod reduce;
-
-pub use self::build::Build;
+mod scheme;
+#[cfg(feature = "serde")]
+mod serialize;
+mod transaction;
+
+pub use self::build::{Build, Provenance};
+pub use self::compact::Remap;
+pub use self::decompile::Decompile;
+pub use self::dot::Dot;
+pub use self::scheme::Scheme;
 pub use self::state::{State, StateId, StateRange, StateSet};
 
 pub(crate) use self::flow::FlowSet;
+pub(crate) use self::transaction::Journal;
 
 use std::collections::HashMap;
 use std::fmt::{self, Debug};
@@ -22,6 +36,8 @@ pub struct Automaton<C: Constructor> {
     pub(crate) states: Vec<State<C>>,
     pub(crate) biunify_cache:
         HashMap<(StateId, StateId), biunify::CacheEntry<C>, BuildHasherDefault<SeaHasher>>,
+    // The undo logs of the transactions in progress, innermost last.
+    pub(crate) journals: Vec<Journal<C>>,
 }
 
 impl<C: Constructor> Automaton<C> {
@@ -29,6 +45,7 @@ impl<C: Constructor> Automaton<C> {
         Automaton {
             states: Vec::new(),
             biunify_cache: HashMap::default(),
+            journals: Vec::new(),
         }
     }
 
@@ -36,10 +53,15 @@ impl<C: Constructor> Automaton<C> {
     where
         I: IntoIterator<Item = (StateId, Polarity)>,
     {
+        let states: Vec<_> = states.into_iter().collect();
+
         let mut reduced = Automaton::new();
-        let range = reduced.reduce(&self, states);
+        let range = reduced.reduce(self, states.iter().copied());
+
+        let mut minimized = Automaton::new();
+        let range = minimized.minimize(&reduced, range.zip(states.iter().map(|&(_, pol)| pol)));
 
-        let offset = self.add_from(&reduced);
+        let offset = self.add_from(&minimized);
 
         #[cfg(debug_assertions)]
         debug_assert!(self.check_flow());
@@ -51,9 +73,7 @@ impl<C: Constructor> Automaton<C> {
         if target_id != source_id {
             let (target, source) = self.index_mut2(target_id, source_id);
 
-            #[cfg(debug_assertions)]
             debug_assert_eq!(target.pol, pol);
-            #[cfg(debug_assertions)]
             debug_assert_eq!(source.pol, pol);
 
             target.cons.merge(&source.cons, pol);
@@ -3,7 +3,7 @@ mod tests;
 
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::mem::replace;
+use std::mem::take;
 
 use small_ord_set::SmallOrdSet;
 
@@ -17,7 +17,6 @@ impl<C: Constructor> State<C> {
         I: IntoIterator<Item = &'a Self>,
     {
         it.into_iter().fold(State::new(pol), |mut l, r| {
-            #[cfg(debug_assertions)]
             debug_assert_eq!(r.pol, pol);
 
             l.cons.merge(&r.cons, pol);
@@ -42,7 +41,6 @@ impl<C: Constructor> Automaton<C> {
         let mut stack: Vec<_> = nfa_ids
             .into_iter()
             .map(|(nfa_id, pol)| {
-                #[cfg(debug_assertions)]
                 debug_assert_eq!(nfa[nfa_id].pol, pol);
 
                 let dfa_id = self.add(nfa[nfa_id].clone());
@@ -57,7 +55,7 @@ impl<C: Constructor> Automaton<C> {
         // Walk transitions and convert to dfa ids.
         while let Some((a, a_pol)) = stack.pop() {
             // Remove old nfa ids
-            let nfa_cons = replace(&mut self[a].cons, ConstructorSet::default());
+            let nfa_cons = take(&mut self[a].cons);
 
             let mut dfa_cons = ConstructorSet::default();
             for nfa_con in nfa_cons.iter() {
@@ -87,7 +85,7 @@ impl<C: Constructor> Automaton<C> {
         // Populate flow
         for &a in map.ns2d.values() {
             // Remove old nfa ids
-            let nfa_flow = replace(&mut self[a].flow, FlowSet::default());
+            let nfa_flow = take(&mut self[a].flow);
 
             let dfa_flow = FlowSet::from_iter(
                 nfa_flow
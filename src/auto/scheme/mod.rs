@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::collections::BTreeMap;
+use std::iter::once;
+
+use crate::auto::{Automaton, State, StateId};
+use crate::{Constructor, Polarity};
+
+/// A type scheme, for ML-style `let` polymorphism.
+///
+/// This is the type of an expression, along with the types it requires of the monomorphic (e.g.
+/// λ-bound) variables it uses. Type variables which occur in neither are implicitly quantified, so
+/// each [instance](Scheme::instantiate) of a scheme is independent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scheme<K> {
+    /// A negative state for the type required of each monomorphic variable.
+    pub env: BTreeMap<K, StateId>,
+    /// A positive state for the type of the expression.
+    pub ty: StateId,
+}
+
+impl<K: Ord + Clone> Scheme<K> {
+    pub fn new(env: BTreeMap<K, StateId>, ty: StateId) -> Self {
+        Scheme { env, ty }
+    }
+
+    /// Creates a self-contained copy of this scheme, whose states are unaffected by any later
+    /// constraints on the original states.
+    ///
+    /// This should be called once the expression has been checked, for example when typing the
+    /// bound expression in `let x = e1 in e2`. The copy is simplified, so it is cheaper to
+    /// [instantiate](Scheme::instantiate).
+    pub fn generalize<C: Constructor>(&self, auto: &mut Automaton<C>) -> Self {
+        // The states are copied together so type variables shared between the environment and
+        // the type remain shared in the copy.
+        let roots = self
+            .env
+            .values()
+            .map(|&id| (id, Polarity::Neg))
+            .chain(once((self.ty, Polarity::Pos)));
+        let mut range = auto.clone_states(roots);
+
+        let env = self.env.keys().cloned().zip(range.by_ref()).collect();
+        let ty = range.next().unwrap();
+        Scheme { env, ty }
+    }
+
+    /// Creates a fresh copy of this scheme, whose type variables are distinct from those of any
+    /// other instance.
+    ///
+    /// The states reachable from the scheme are copied as they are, without simplifying them.
+    /// The environment of the instance still needs to be combined with that of the expression
+    /// it is used in, since monomorphic variables are shared by every instance.
+    pub fn instantiate<C: Constructor>(&self, auto: &mut Automaton<C>) -> Self {
+        let ids = copy_reachable(auto, self.env.values().copied().chain(once(self.ty)));
+
+        let env = self
+            .env
+            .iter()
+            .map(|(key, id)| (key.clone(), ids[id]))
+            .collect();
+        Scheme {
+            env,
+            ty: ids[&self.ty],
+        }
+    }
+}
+
+// Appends a copy of the states reachable from `roots`, returning the id of the copy of each one.
+// Flow edges to states which are not copied are dropped.
+fn copy_reachable<C, I>(auto: &mut Automaton<C>, roots: I) -> HashMap<StateId, StateId>
+where
+    C: Constructor,
+    I: IntoIterator<Item = StateId>,
+{
+    let start = auto.next();
+    let mut ids = HashMap::new();
+    let mut reachable = Vec::new();
+    let mut stack: Vec<_> = roots.into_iter().collect();
+    while let Some(id) = stack.pop() {
+        if let Entry::Vacant(entry) = ids.entry(id) {
+            entry.insert(start.shift(reachable.len() as u32));
+            reachable.push(id);
+            for con in auto[id].cons.iter() {
+                con.visit_params(|_, set| stack.extend(set));
+            }
+        }
+    }
+
+    for id in reachable {
+        let state = &auto[id];
+        let state = State {
+            pol: state.pol,
+            cons: state.cons.clone().remap(|id| ids[&id]),
+            flow: state.flow.clone().filter_map(|id| ids.get(&id).copied()),
+        };
+        auto.add(state);
+    }
+
+    #[cfg(debug_assertions)]
+    debug_assert!(auto.check_flow());
+
+    ids
+}
@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+
+use crate::auto::{Automaton, Scheme, StateId};
+use crate::polar::Ty;
+use crate::tests::{Constructed, Constructor};
+use crate::Polarity;
+
+fn parse(input: &str) -> Ty<Constructed, char> {
+    Ty::parse(input).unwrap()
+}
+
+fn build(auto: &mut Automaton<Constructor>, pol: Polarity, ty: &str) -> StateId {
+    auto.builder::<char>().build_polar(pol, &parse(ty))
+}
+
+#[allow(clippy::result_large_err)]
+fn check(auto: &mut Automaton<Constructor>, id: StateId, ty: &str) -> bool {
+    let rhs = build(auto, Polarity::Neg, ty);
+    auto.transaction(|auto| auto.biunify(id, rhs)).is_ok()
+}
+
+#[test]
+fn instantiate_independent() {
+    let mut auto = Automaton::new();
+    let id = build(&mut auto, Polarity::Pos, "'a -> 'a");
+    let scheme = Scheme::<char>::new(BTreeMap::new(), id).generalize(&mut auto);
+
+    let a = scheme.instantiate(&mut auto);
+    let b = scheme.instantiate(&mut auto);
+    assert!(check(&mut auto, a.ty, "bool -> bool"));
+    assert!(check(&mut auto, b.ty, "{} -> {}"));
+
+    // Without instantiating, both uses constrain the same type variable.
+    assert!(check(&mut auto, id, "bool -> bool"));
+    assert!(!check(&mut auto, id, "{} -> {}"));
+}
+
+#[test]
+fn instantiate_env() {
+    let mut auto = Automaton::new();
+    let mut builder = auto.builder::<char>();
+    let x = builder.build_polar(Polarity::Neg, &parse("'a"));
+    let ty = builder.build_polar(Polarity::Pos, &parse("'b -> 'a"));
+    drop(builder);
+
+    let mut env = BTreeMap::new();
+    env.insert('x', x);
+    let scheme = Scheme::new(env, ty).generalize(&mut auto);
+
+    // The type variable shared by the environment and type remains shared in each instance.
+    let inst = scheme.instantiate(&mut auto);
+    let bool = build(&mut auto, Polarity::Pos, "bool");
+    auto.biunify(bool, inst.env[&'x']).unwrap();
+    assert!(check(&mut auto, inst.ty, "{} -> bool"));
+    assert!(!check(&mut auto, inst.ty, "{} -> {}"));
+
+    let inst = scheme.instantiate(&mut auto);
+    assert!(check(&mut auto, inst.ty, "{} -> {}"));
+}
+
+#[test]
+fn generalize_snapshot() {
+    let mut auto = Automaton::new();
+    let id = build(&mut auto, Polarity::Pos, "'a -> 'a");
+    let scheme = Scheme::<char>::new(BTreeMap::new(), id).generalize(&mut auto);
+
+    // Later constraints on the original states do not affect the scheme.
+    assert!(check(&mut auto, id, "bool -> bool"));
+    let inst = scheme.instantiate(&mut auto);
+    assert!(check(&mut auto, inst.ty, "{} -> {}"));
+}
+
+#[test]
+fn instantiate_generalized() {
+    let mut auto = Automaton::new();
+    // The unrolled type is simplified once, when generalizing.
+    let id = build(&mut auto, Polarity::Pos, "bool -> rec a. bool -> a");
+    let scheme = Scheme::<char>::new(BTreeMap::new(), id).generalize(&mut auto);
+
+    let len = auto.states.len();
+    let inst = scheme.instantiate(&mut auto);
+    assert_eq!(auto.states.len(), len + 2);
+    assert!(check(&mut auto, inst.ty, "bool -> bool -> bool -> top"));
+    assert!(!check(&mut auto, inst.ty, "bool -> bool -> {} -> top"));
+}
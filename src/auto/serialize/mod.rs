@@ -0,0 +1,152 @@
+//! Serialization of automata, enabled by the `serde` feature.
+//!
+//! Sets of states and constructors are encoded as plain sequences, and each state is written
+//! with its polarity, which is checked when deserializing. The biunification cache is not
+//! serialized, so a deserialized automaton will recheck constraints it has already solved.
+
+#[cfg(test)]
+mod tests;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde::de::{self, Deserializer};
+use serde::ser::{SerializeStruct, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::auto::{Automaton, FlowSet, State, StateId, StateSet};
+use crate::{Constructor, ConstructorSet, Polarity};
+
+impl Serialize for StateSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for StateSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut ids = Vec::<StateId>::deserialize(deserializer)?.into_iter();
+        let mut set = match ids.next() {
+            Some(id) => StateSet::new(id),
+            None => return Err(de::Error::invalid_length(0, &"a non-empty set of states")),
+        };
+        for id in ids {
+            set.insert(id);
+        }
+        Ok(set)
+    }
+}
+
+impl Serialize for FlowSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Sort the states so the output does not depend on the hash set's iteration order.
+        let mut ids: Vec<_> = self.iter().collect();
+        ids.sort();
+        serializer.collect_seq(ids)
+    }
+}
+
+impl<'de> Deserialize<'de> for FlowSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(FlowSet::from_iter(Vec::<StateId>::deserialize(
+            deserializer,
+        )?))
+    }
+}
+
+impl<C> Serialize for ConstructorSet<C>
+where
+    C: Constructor + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, C> Deserialize<'de> for ConstructorSet<C>
+where
+    C: Constructor + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut set = ConstructorSet::default();
+        for con in Vec::<C>::deserialize(deserializer)? {
+            if set.get(con.component()).is_some() {
+                return Err(de::Error::custom(
+                    "found more than one constructor with the same component",
+                ));
+            }
+            // The polarity is only used to join constructors with the same component.
+            set.add(Polarity::Pos, Cow::Owned(con));
+        }
+        Ok(set)
+    }
+}
+
+impl<C> Serialize for Automaton<C>
+where
+    C: Constructor + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let states: Vec<_> = self
+            .states
+            .iter()
+            .map(|state| StateRef {
+                pol: state.pol,
+                cons: &state.cons,
+                flow: &state.flow,
+            })
+            .collect();
+
+        let mut auto = serializer.serialize_struct("Automaton", 1)?;
+        auto.serialize_field("states", &states)?;
+        auto.end()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "State", bound = "C: Serialize")]
+struct StateRef<'a, C: Constructor> {
+    pol: Polarity,
+    cons: &'a ConstructorSet<C>,
+    flow: &'a FlowSet,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "State", bound = "C: Deserialize<'de>")]
+struct StateDef<C: Constructor> {
+    pol: Polarity,
+    cons: ConstructorSet<C>,
+    flow: FlowSet,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Automaton", bound = "C: Deserialize<'de>")]
+struct AutomatonDef<C: Constructor> {
+    states: Vec<StateDef<C>>,
+}
+
+/// Deserializes an automaton, checking that every state it refers to exists, that each type
+/// parameter has the polarity required by its label, and that flow edges are symmetric and
+/// between states of opposite polarity.
+impl<'de, C> Deserialize<'de> for Automaton<C>
+where
+    C: Constructor + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let AutomatonDef { states } = AutomatonDef::deserialize(deserializer)?;
+        let auto = Automaton {
+            states: states
+                .into_iter()
+                .map(|state| State {
+                    pol: state.pol,
+                    cons: state.cons,
+                    flow: state.flow,
+                })
+                .collect(),
+            biunify_cache: HashMap::default(),
+            journals: Vec::new(),
+        };
+        auto.validate().map_err(de::Error::custom)?;
+        Ok(auto)
+    }
+}
@@ -0,0 +1,103 @@
+use std::iter::once;
+
+use crate::auto::{Automaton, StateId};
+use crate::polar::Ty;
+use crate::tests::{Constructed, Constructor};
+use crate::Polarity;
+
+fn build(ty: &str, pol: Polarity) -> (Automaton<Constructor>, StateId) {
+    let ty: Ty<Constructed, char> = Ty::parse(ty).unwrap();
+    let mut nfa = Automaton::new();
+    let id = nfa.builder().build_polar(pol, &ty);
+
+    let mut dfa = Automaton::new();
+    let id = dfa.reduce(&nfa, once((id, pol))).next().unwrap();
+    (dfa, id)
+}
+
+fn deserialize(json: &str) -> Result<Automaton<Constructor>, String> {
+    serde_json::from_str(json).map_err(|err| err.to_string())
+}
+
+#[test]
+fn roundtrip() {
+    let (auto, id) = build("rec a. {x: 'a -> 'a, y: a} | [A: bool]", Polarity::Pos);
+    let json = serde_json::to_string(&auto).unwrap();
+    let copy = deserialize(&json).unwrap();
+
+    assert_eq!(serde_json::to_string(&copy).unwrap(), json);
+    let ty: Ty<Constructed<usize>, usize> = auto.to_polar_ty(id, Polarity::Pos);
+    assert_eq!(copy.to_polar_ty(id, Polarity::Pos), ty);
+}
+
+#[test]
+fn roundtrip_neg() {
+    let (auto, id) = build("{x: 'a} & ('a -> bool)", Polarity::Neg);
+    let copy = deserialize(&serde_json::to_string(&auto).unwrap()).unwrap();
+
+    let ty: Ty<Constructed<usize>, usize> = auto.to_polar_ty(id, Polarity::Neg);
+    assert_eq!(copy.to_polar_ty(id, Polarity::Neg), ty);
+}
+
+#[test]
+fn format() {
+    let (auto, _) = build("'a -> 'a", Polarity::Pos);
+    assert_eq!(
+        serde_json::to_string(&auto).unwrap(),
+        r#"{"states":[{"pol":"Pos","cons":[{"Fun":[[1],[2]]}],"flow":[]},{"pol":"Neg","cons":[],"flow":[2]},{"pol":"Pos","cons":[],"flow":[1]}]}"#
+    );
+}
+
+#[test]
+fn roundtrip_exact() {
+    // Nothing links the polarity of this state to another one, so it can only be written
+    // correctly by recording it. Debug and release builds both write and read this exact JSON.
+    let (auto, _) = build("bool", Polarity::Neg);
+    let json = r#"{"states":[{"pol":"Neg","cons":["Bool"],"flow":[]}]}"#;
+    assert_eq!(serde_json::to_string(&auto).unwrap(), json);
+
+    let copy = deserialize(json).unwrap();
+    assert_eq!(format!("{:?}", copy.states), format!("{:?}", auto.states));
+}
+
+#[test]
+fn polar_ty() {
+    let ty: Ty<Constructed, char> = Ty::parse("rec a. {x: 'a -> a} | bool").unwrap();
+    let json = serde_json::to_string(&ty).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Ty<Constructed, char>>(&json).unwrap(),
+        ty
+    );
+}
+
+fn error(json: &str) -> String {
+    deserialize(json).unwrap_err()
+}
+
+#[test]
+fn invalid() {
+    assert!(
+        error(r#"{"states":[{"pol":"Pos","cons":[{"Fun":[[1],[1]]}],"flow":[]}]}"#)
+            .starts_with("state 0 refers to state 1, which does not exist")
+    );
+    assert!(error(
+        r#"{"states":[{"pol":"Pos","cons":[{"Fun":[[1],[1]]}],"flow":[]},{"pol":"Pos","cons":[],"flow":[]}]}"#
+    )
+    .starts_with("state 0 has a type parameter 1 of the wrong polarity"));
+    assert!(error(
+        r#"{"states":[{"pol":"Neg","cons":[],"flow":[1]},{"pol":"Pos","cons":[],"flow":[]}]}"#
+    )
+    .starts_with("state 0 has a flow edge to state 1, but not the reverse"));
+    assert!(error(
+        r#"{"states":[{"pol":"Neg","cons":[],"flow":[1]},{"pol":"Neg","cons":[],"flow":[0]}]}"#
+    )
+    .starts_with("states 0 and 1 have a flow edge but the same polarity"));
+    assert!(
+        error(r#"{"states":[{"pol":"Pos","cons":["Bool","Bool"],"flow":[]}]}"#)
+            .starts_with("found more than one constructor with the same component")
+    );
+    assert!(
+        error(r#"{"states":[{"pol":"Pos","cons":[{"Fun":[[],[0]]}],"flow":[]}]}"#)
+            .starts_with("invalid length 0, expected a non-empty set of states")
+    );
+}
@@ -2,25 +2,35 @@ mod set;
 
 pub use self::set::StateSet;
 
+use std::fmt;
 use std::ops::{Index, IndexMut, Range};
 
 use crate::auto::{Automaton, ConstructorSet, FlowSet};
-use crate::{Constructor, Polarity};
+use crate::{Constructor, Label, Polarity};
 
 #[derive(Debug)]
 pub struct State<C: Constructor> {
-    #[cfg(debug_assertions)]
     pub(crate) pol: Polarity,
     pub(crate) cons: ConstructorSet<C>,
     pub(crate) flow: FlowSet,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
-pub struct StateId(u32);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateId(pub(in crate::auto) u32);
 
 #[derive(Debug, Clone)]
 pub struct StateRange(Range<u32>);
 
+/// A reason the states of an automaton loaded from outside the process are not well-formed.
+#[derive(Debug)]
+pub(in crate::auto) enum InvalidState {
+    NotFound(StateId, StateId),
+    Polarity(StateId, StateId),
+    FlowPolarity(StateId, StateId),
+    FlowSymmetry(StateId, StateId),
+}
+
 impl StateId {
     pub fn as_u32(self) -> u32 {
         self.0
@@ -32,10 +42,9 @@ impl StateId {
 }
 
 impl<C: Constructor> State<C> {
-    pub(crate) fn new(_pol: Polarity) -> Self {
+    pub(crate) fn new(pol: Polarity) -> Self {
         State {
-            #[cfg(debug_assertions)]
-            pol: _pol,
+            pol,
             cons: ConstructorSet::default(),
             flow: FlowSet::default(),
         }
@@ -51,7 +60,6 @@ impl<C: Constructor> State<C> {
 
     fn shift(self, offset: u32) -> Self {
         State {
-            #[cfg(debug_assertions)]
             pol: self.pol,
             cons: self.cons.shift(offset),
             flow: self.flow.shift(offset),
@@ -62,7 +70,6 @@ impl<C: Constructor> State<C> {
 impl<C: Constructor> Clone for State<C> {
     fn clone(&self) -> Self {
         State {
-            #[cfg(debug_assertions)]
             pol: self.pol,
             cons: self.cons.clone(),
             flow: self.flow.clone(),
@@ -99,6 +106,8 @@ impl<C: Constructor> Automaton<C> {
         StateId(j): StateId,
     ) -> (&mut State<C>, &mut State<C>) {
         debug_assert_ne!(i, j);
+        self.journal_state(StateId(i));
+        self.journal_state(StateId(j));
         if i < j {
             let (l, r) = self.states.split_at_mut(j as usize);
             (&mut l[i as usize], &mut r[0])
@@ -120,6 +129,44 @@ impl<C: Constructor> Automaton<C> {
     }
 }
 
+impl<C: Constructor> Automaton<C> {
+    /// Checks that every state referred to exists, that each type parameter has the polarity
+    /// required by its label, and that flow edges are symmetric and between states of opposite
+    /// polarity.
+    pub(in crate::auto) fn validate(&self) -> Result<(), InvalidState> {
+        let len = self.states.len() as u32;
+        self.enumerate().try_for_each(|(id, state)| {
+            let mut result = Ok(());
+            for con in state.cons.iter() {
+                con.visit_params(|label, set| {
+                    for param in set {
+                        if result.is_err() {
+                            return;
+                        } else if param.0 >= len {
+                            result = Err(InvalidState::NotFound(id, param));
+                        } else if self[param].pol != state.pol * label.polarity() {
+                            result = Err(InvalidState::Polarity(id, param));
+                        }
+                    }
+                });
+            }
+            result?;
+
+            state.flow.iter().try_for_each(|other| {
+                if other.0 >= len {
+                    Err(InvalidState::NotFound(id, other))
+                } else if self[other].pol == state.pol {
+                    Err(InvalidState::FlowPolarity(id, other))
+                } else if !self[other].flow.iter().any(|back| back == id) {
+                    Err(InvalidState::FlowSymmetry(id, other))
+                } else {
+                    Ok(())
+                }
+            })
+        })
+    }
+}
+
 impl<C: Constructor> Index<StateId> for Automaton<C> {
     type Output = State<C>;
 
@@ -130,6 +177,7 @@ impl<C: Constructor> Index<StateId> for Automaton<C> {
 
 impl<C: Constructor> IndexMut<StateId> for Automaton<C> {
     fn index_mut(&mut self, StateId(id): StateId) -> &mut Self::Output {
+        self.journal_state(StateId(id));
         self.states.index_mut(id as usize)
     }
 }
@@ -147,3 +195,30 @@ impl Iterator for StateRange {
         self.0.next().map(StateId)
     }
 }
+
+impl fmt::Display for InvalidState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidState::NotFound(id, other) => write!(
+                f,
+                "state {} refers to state {}, which does not exist",
+                id.0, other.0
+            ),
+            InvalidState::Polarity(id, param) => write!(
+                f,
+                "state {} has a type parameter {} of the wrong polarity",
+                id.0, param.0
+            ),
+            InvalidState::FlowPolarity(id, other) => write!(
+                f,
+                "states {} and {} have a flow edge but the same polarity",
+                id.0, other.0
+            ),
+            InvalidState::FlowSymmetry(id, other) => write!(
+                f,
+                "state {} has a flow edge to state {}, but not the reverse",
+                id.0, other.0
+            ),
+        }
+    }
+}
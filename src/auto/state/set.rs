@@ -26,7 +26,7 @@ impl StateSet {
         self.set.extend(other)
     }
 
-    pub fn iter(&self) -> Copied<slice::Iter<StateId>> {
+    pub fn iter(&self) -> Copied<slice::Iter<'_, StateId>> {
         self.set.iter().copied()
     }
 
@@ -36,6 +36,15 @@ impl StateSet {
         }
     }
 
+    pub(crate) fn remap<F>(self, mapper: F) -> Self
+    where
+        F: FnMut(StateId) -> StateId,
+    {
+        StateSet {
+            set: self.set.into_iter().map(mapper).collect(),
+        }
+    }
+
     pub(crate) fn unwrap_reduced(&self) -> StateId {
         debug_assert_eq!(self.set.len(), 1);
         self.set[0]
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use crate::auto::{Automaton, State, StateId};
+use crate::Constructor;
+
+// The changes made during a transaction, which are undone if it fails.
+pub(crate) struct Journal<C: Constructor> {
+    // The number of states when the transaction started.
+    len: usize,
+    // The contents of states which existed when the transaction started, before it first
+    // modified them.
+    states: HashMap<StateId, State<C>>,
+    // The constraints added to the biunify cache during the transaction.
+    cache: Vec<(StateId, StateId)>,
+}
+
+impl<C: Constructor> Automaton<C> {
+    /// Runs `f`, restoring the automaton to its previous state if it returns an error.
+    ///
+    /// This allows checking to continue after a failed call to
+    /// [`biunify`](Automaton::biunify), which may otherwise leave the automaton partially
+    /// unified. States added by a failed transaction are discarded, so their ids must not be
+    /// used after it returns. Transactions may be nested.
+    ///
+    /// Starting a transaction is cheap: only the states it modifies are copied, the first time
+    /// they are modified.
+    pub fn transaction<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Self) -> Result<T, E>,
+    {
+        self.journals.push(Journal {
+            len: self.states.len(),
+            states: HashMap::new(),
+            cache: Vec::new(),
+        });
+
+        let result = f(self);
+        let journal = self.journals.pop().unwrap();
+        if result.is_err() {
+            self.rollback(journal);
+        } else if let Some(outer) = self.journals.last_mut() {
+            outer.commit(journal);
+        }
+        result
+    }
+
+    // Records the contents of a state before it is modified by the current transaction.
+    pub(in crate::auto) fn journal_state(&mut self, id: StateId) {
+        if let Some(journal) = self.journals.last_mut() {
+            if (id.0 as usize) < journal.len {
+                let states = &self.states;
+                journal
+                    .states
+                    .entry(id)
+                    .or_insert_with(|| states[id.0 as usize].clone());
+            }
+        }
+    }
+
+    fn rollback(&mut self, journal: Journal<C>) {
+        self.states.truncate(journal.len);
+        for (id, state) in journal.states {
+            self.states[id.0 as usize] = state;
+        }
+        for constraint in &journal.cache {
+            self.biunify_cache.remove(constraint);
+        }
+    }
+}
+
+impl<C: Constructor> Journal<C> {
+    pub(crate) fn record_constraint(&mut self, constraint: (StateId, StateId)) {
+        self.cache.push(constraint);
+    }
+
+    // Keeps the changes of a nested transaction which succeeded, so they are undone if this one
+    // fails. States this transaction already recorded were not modified in between, and states
+    // it added are discarded anyway.
+    fn commit(&mut self, inner: Journal<C>) {
+        for (id, state) in inner.states {
+            if (id.0 as usize) < self.len {
+                self.states.entry(id).or_insert(state);
+            }
+        }
+        self.cache.extend(inner.cache);
+    }
+}
@@ -3,27 +3,41 @@ mod reference;
 #[cfg(test)]
 mod tests;
 
+use std::collections::hash_map::Entry;
 use std::convert::Infallible;
 use std::fmt::{self, Debug};
 use std::iter::once;
 
-use crate::auto::{Automaton, StateId};
+use crate::auto::{Automaton, Provenance, StateId};
 use crate::{Constructor, Label, Polarity};
 
 pub type Result<C> = std::result::Result<(), Error<C>>;
 
+/// An incompatible pair of constructors found during biunification.
 #[derive(Debug)]
 pub struct Error<C: Constructor> {
-    pub stack: Vec<(C::Label, C, C)>,
-    pub constraint: (C, C),
+    /// The constraints which required `constraint`, from innermost to outermost, along with the
+    /// label of the type parameter each one was propagated through.
+    pub stack: Vec<(C::Label, Side<C>, Side<C>)>,
+    /// The positive and negative constructors which are not compatible.
+    pub constraint: (Side<C>, Side<C>),
 }
 
+/// One side of a constraint in a [biunification error](Error): a state and the constructor
+/// it was checked with.
+#[derive(Debug, Clone)]
+pub struct Side<C: Constructor> {
+    pub id: StateId,
+    pub con: C,
+}
+
+#[derive(Clone)]
 pub(crate) enum CacheEntry<C: Constructor> {
     Root,
     RequiredBy {
         label: C::Label,
-        pos: (StateId, C),
-        neg: (StateId, C),
+        pos: Side<C>,
+        neg: Side<C>,
     },
 }
 
@@ -35,83 +49,220 @@ impl<C: Constructor> Automaton<C> {
 
     /// Solves a set of constraints t⁺ ≤ t⁻ where t⁺ and t⁻ are represented by the states `qp` and `qn`.
     pub fn biunify_all<I>(&mut self, constraints: I) -> Result<C>
+    where
+        I: IntoIterator<Item = (StateId, StateId)>,
+    {
+        let mut errors = Vec::new();
+        self.biunify_all_impl(constraints, &mut errors, true);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Solves a set of constraints like [`biunify_all`](Automaton::biunify_all), but continues
+    /// after an incompatible pair of constructors is found, returning an error for each one.
+    pub fn biunify_all_errors<I>(
+        &mut self,
+        constraints: I,
+    ) -> std::result::Result<(), Vec<Error<C>>>
+    where
+        I: IntoIterator<Item = (StateId, StateId)>,
+    {
+        let mut errors = Vec::new();
+        self.biunify_all_impl(constraints, &mut errors, false);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn biunify_all_impl<I>(&mut self, constraints: I, errors: &mut Vec<Error<C>>, fail_fast: bool)
     where
         I: IntoIterator<Item = (StateId, StateId)>,
     {
         let mut stack = Vec::with_capacity(20);
+        let biunify_cache = &mut self.biunify_cache;
+        let mut journal = self.journals.last_mut();
         stack.extend(constraints.into_iter().filter(|&constraint| {
-            self.biunify_cache
-                .insert(constraint, CacheEntry::Root)
-                .is_none()
+            if biunify_cache.insert(constraint, CacheEntry::Root).is_some() {
+                return false;
+            }
+            if let Some(journal) = journal.as_mut() {
+                journal.record_constraint(constraint);
+            }
+            true
         }));
+        let mut flow = Vec::new();
         while let Some(constraint) = stack.pop() {
-            self.biunify_impl(&mut stack, constraint)?;
+            self.biunify_impl(&mut stack, &mut flow, errors, fail_fast, constraint);
+            if fail_fast && !errors.is_empty() {
+                return;
+            }
         }
-        Ok(())
     }
 
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
     fn biunify_impl(
         &mut self,
         stack: &mut Vec<(StateId, StateId)>,
+        flow: &mut Vec<StateId>,
+        errors: &mut Vec<Error<C>>,
+        fail_fast: bool,
         (qp, qn): (StateId, StateId),
-    ) -> Result<C> {
-        #[cfg(debug_assertions)]
+    ) {
         debug_assert_eq!(self[qp].pol, Polarity::Pos);
-        #[cfg(debug_assertions)]
         debug_assert_eq!(self[qn].pol, Polarity::Neg);
         debug_assert!(self.biunify_cache.contains_key(&(qp, qn)));
 
         for (cp, cn) in product(self[qp].cons.iter(), self[qn].cons.iter()) {
             if !(cp <= cn) {
-                return Err(self.make_error((qp, cp.clone()), (qn, cn.clone())));
+                errors.push(self.make_error(Side::new(qp, cp), Side::new(qn, cn)));
+                if fail_fast {
+                    return;
+                }
             }
         }
-        for to in self[qn].flow.iter() {
+        // Merging may update the flow sets of `qp` and `qn`, so their edges are collected into a
+        // buffer, which is reused between calls, before merging.
+        flow.clear();
+        flow.extend(self[qn].flow.iter());
+        for &to in flow.iter() {
             self.merge(Polarity::Pos, to, qp);
         }
-        for from in self[qp].flow.iter() {
+        flow.clear();
+        flow.extend(self[qp].flow.iter());
+        for &from in flow.iter() {
             self.merge(Polarity::Neg, from, qn);
         }
 
         let states = &self.states;
         let biunify_cache = &mut self.biunify_cache;
+        let mut journal = self.journals.last_mut();
         let cps = &states[qp.as_u32() as usize].cons;
         let cns = &states[qn.as_u32() as usize].cons;
         for (cp, cn) in cps.intersection(cns) {
-            cp.visit_params_intersection::<_, Infallible>(&cn, |label, l, r| {
+            cp.visit_params_intersection::<_, Infallible>(cn, |label, l, r| {
                 let (ps, ns) = label.polarity().flip(l, r);
                 stack.extend(product(ps, ns).filter(|&constraint| {
-                    biunify_cache
-                        .insert(
-                            constraint,
-                            CacheEntry::RequiredBy {
+                    // Existing entries must not be overwritten, or the chain of constraints
+                    // followed by `make_error` may contain a cycle.
+                    match biunify_cache.entry(constraint) {
+                        Entry::Occupied(_) => false,
+                        Entry::Vacant(entry) => {
+                            if let Some(journal) = journal.as_mut() {
+                                journal.record_constraint(constraint);
+                            }
+                            entry.insert(CacheEntry::RequiredBy {
                                 label: label.clone(),
-                                pos: (qp, cp.clone()),
-                                neg: (qn, cn.clone()),
-                            },
-                        )
-                        .is_none()
+                                pos: Side::new(qp, cp),
+                                neg: Side::new(qn, cn),
+                            });
+                            true
+                        }
+                    }
                 }));
                 Ok(())
             })
             .unwrap();
         }
-        Ok(())
     }
 
-    fn make_error(&self, pos: (StateId, C), neg: (StateId, C)) -> Error<C> {
+    fn make_error(&self, pos: Side<C>, neg: Side<C>) -> Error<C> {
         let mut stack = Vec::new();
 
-        let mut key = (pos.0, neg.0);
+        let mut key = (pos.id, neg.id);
         while let CacheEntry::RequiredBy { label, pos, neg } = &self.biunify_cache[&key] {
-            stack.push((label.clone(), pos.1.clone(), neg.1.clone()));
-            key = (pos.0, neg.0);
+            stack.push((label.clone(), pos.clone(), neg.clone()));
+            key = (pos.id, neg.id);
         }
 
         Error {
             stack,
-            constraint: (pos.1, neg.1),
+            constraint: (pos, neg),
+        }
+    }
+}
+
+impl<C: Constructor> Error<C> {
+    /// Gets the states of the constraint passed to [`biunify_all`](Automaton::biunify_all) which
+    /// led to this error.
+    pub fn root(&self) -> (StateId, StateId) {
+        let (pos, neg) = match self.stack.last() {
+            Some((_, pos, neg)) => (pos, neg),
+            None => (&self.constraint.0, &self.constraint.1),
+        };
+        (pos.id, neg.id)
+    }
+
+    /// Gets the positive and negative states involved in this error, from the failing
+    /// constraint outwards.
+    pub fn states(&self) -> impl Iterator<Item = (StateId, StateId)> + '_ {
+        let (pos, neg) = &self.constraint;
+        once((pos.id, neg.id)).chain(self.stack.iter().map(|(_, pos, neg)| (pos.id, neg.id)))
+    }
+
+    /// Finds the provenance of the innermost positive and negative states for which some was
+    /// recorded, such as the source locations of the expressions whose types clashed.
+    pub fn provenance<'a, P>(
+        &self,
+        provenance: &'a Provenance<P>,
+    ) -> (Option<&'a P>, Option<&'a P>) {
+        let pos = self.states().find_map(|(pos, _)| provenance.get(pos));
+        let neg = self.states().find_map(|(_, neg)| provenance.get(neg));
+        (pos, neg)
+    }
+
+    /// Returns a human-readable explanation of this error, using `fmt_constructor` and
+    /// `fmt_label` to print constructors and type parameters. The result looks like
+    /// "expected bool, found {x: ...} in field `x` of the argument".
+    pub fn display<'a, F, G>(&'a self, fmt_constructor: F, fmt_label: G) -> impl fmt::Display + 'a
+    where
+        F: Fn(&C, &mut fmt::Formatter) -> fmt::Result + 'a,
+        G: Fn(&C::Label, &mut fmt::Formatter) -> fmt::Result + 'a,
+    {
+        ErrorDisplay {
+            error: self,
+            fmt_constructor,
+            fmt_label,
+        }
+    }
+}
+
+impl<C: Constructor> Side<C> {
+    fn new(id: StateId, con: &C) -> Self {
+        Side {
+            id,
+            con: con.clone(),
+        }
+    }
+}
+
+struct ErrorDisplay<'a, C: Constructor, F, G> {
+    error: &'a Error<C>,
+    fmt_constructor: F,
+    fmt_label: G,
+}
+
+impl<'a, C, F, G> fmt::Display for ErrorDisplay<'a, C, F, G>
+where
+    C: Constructor,
+    F: Fn(&C, &mut fmt::Formatter) -> fmt::Result,
+    G: Fn(&C::Label, &mut fmt::Formatter) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (pos, neg) = &self.error.constraint;
+        f.write_str("expected ")?;
+        (self.fmt_constructor)(&neg.con, f)?;
+        f.write_str(", found ")?;
+        (self.fmt_constructor)(&pos.con, f)?;
+
+        for (idx, (label, _, _)) in self.error.stack.iter().enumerate() {
+            f.write_str(if idx == 0 { " in " } else { " of " })?;
+            (self.fmt_label)(label, f)?;
         }
+        Ok(())
     }
 }
 
@@ -123,7 +274,7 @@ where
     J: Clone,
 {
     lhs.into_iter()
-        .flat_map(move |l| rhs.clone().into_iter().map(move |r| (l.clone(), r)))
+        .flat_map(move |l| rhs.clone().into_iter().map(move |r| (l, r)))
 }
 
 impl<C> Debug for CacheEntry<C>
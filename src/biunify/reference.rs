@@ -5,7 +5,7 @@ use im::Vector;
 use proptest::strategy::Strategy;
 
 use crate::polar::Ty;
-use crate::tests::{arb_polar_ty, Constructed};
+use crate::tests::{self, Constructed};
 use crate::Polarity;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -15,7 +15,11 @@ pub(in crate::biunify) struct Constraint(
 );
 
 pub(in crate::biunify) fn arb_constraint() -> impl Strategy<Value = Constraint> {
-    (arb_polar_ty(Polarity::Pos), arb_polar_ty(Polarity::Neg)).prop_map(|(l, r)| Constraint(l, r))
+    tests::arb_constraint().prop_map(|(l, r)| Constraint(l, r))
+}
+
+pub(in crate::biunify) fn arb_variant_constraint() -> impl Strategy<Value = Constraint> {
+    tests::arb_variant_constraint().prop_map(|(l, r)| Constraint(l, r))
 }
 
 impl Constraint {
@@ -48,6 +52,19 @@ fn subi(con: Constraint) -> Result<Vec<Constraint>, ()> {
                 Err(())
             }
         }
+        Constraint(
+            Ty::Constructed(Constructed::Variant(t1)),
+            Ty::Constructed(Constructed::Variant(t2)),
+        ) => {
+            if iter_set::difference(t1.keys(), t2.keys()).next().is_none() {
+                Ok(t1
+                    .keys()
+                    .map(|key| Constraint(*t1[key].clone(), *t2[key].clone()))
+                    .collect())
+            } else {
+                Err(())
+            }
+        }
         Constraint(Ty::Recursive(lhs), rhs) => {
             let lhs = subst((*lhs).clone(), 0, Ty::Recursive(lhs));
             Ok(vec![Constraint(lhs, rhs)])
@@ -153,6 +170,11 @@ fn subst(
                 .map(|(k, v)| (k, Box::new(subst(*v, var, sub.clone()))))
                 .collect(),
         )),
+        Ty::Constructed(Constructed::Variant(tags)) => Ty::Constructed(Constructed::Variant(
+            tags.into_iter()
+                .map(|(k, v)| (k, Box::new(subst(*v, var, sub.clone()))))
+                .collect(),
+        )),
         _ => ty,
     }
 }
@@ -183,6 +205,11 @@ fn bisubst(
                 .map(|(k, v)| (k, Box::new(bisubst(*v, pol, var, sub.clone()))))
                 .collect(),
         )),
+        Ty::Constructed(Constructed::Variant(tags)) => Ty::Constructed(Constructed::Variant(
+            tags.into_iter()
+                .map(|(k, v)| (k, Box::new(bisubst(*v, pol, var, sub.clone()))))
+                .collect(),
+        )),
         _ => ty,
     }
 }
@@ -222,7 +249,8 @@ fn shift(ty: &mut Ty<Constructed, char>, n: usize) {
             shift(d, n);
             shift(r, n);
         }
-        Ty::Constructed(Constructed::Record(fields)) => {
+        Ty::Constructed(Constructed::Record(fields))
+        | Ty::Constructed(Constructed::Variant(fields)) => {
             fields.values_mut().for_each(|t| shift(t, n))
         }
         Ty::Recursive(t) => shift(t, n),
@@ -1,19 +1,23 @@
+use std::collections::BTreeMap;
+use std::iter::once;
+use std::rc::Rc;
+
 use itertools::Itertools;
 use proptest::collection::vec;
 use proptest::test_runner::Config;
 use proptest::{prop_assert_eq, proptest};
 
-use crate::auto::Automaton;
-use crate::biunify::reference::{self, arb_constraint};
+use crate::auto::{Automaton, Provenance, StateId, StateSet};
+use crate::biunify::reference::{self, arb_constraint, arb_variant_constraint, Constraint};
 use crate::polar::Ty;
-use crate::tests::Constructed;
+use crate::tests::{Constructed, Constructor, Label};
 use crate::Polarity;
 
 #[test]
 fn constructed() {
     let mut auto = Automaton::new();
 
-    let mut builder = auto.builder();
+    let mut builder = auto.builder::<char>();
     let lhs_id = builder.build_polar(
         Polarity::Pos,
         &Ty::Constructed(Constructed::Record(Default::default())),
@@ -30,6 +34,85 @@ fn constructed() {
     assert!(auto.biunify(lhs_id, rhs_id).is_err());
 }
 
+#[test]
+fn error_recursive() {
+    // The constraint on `y` requires the original constraint again, which must still be
+    // reported as the root of the error on `x`.
+    let record = |x| {
+        Ty::Recursive(Box::new(Ty::Constructed(Constructed::Record(
+            vec![
+                (Rc::from("x"), Box::new(x)),
+                (Rc::from("y"), Box::new(Ty::BoundVar(0))),
+            ]
+            .into_iter()
+            .collect(),
+        ))))
+    };
+    let lhs = record(Ty::Constructed(Constructed::Bool));
+    let rhs = record(Ty::Constructed(Constructed::Record(Default::default())));
+
+    let mut auto = Automaton::new();
+
+    let mut builder = auto.builder::<char>();
+    let lhs_id = builder.build_polar(Polarity::Pos, &lhs);
+    let rhs_id = builder.build_polar(Polarity::Neg, &rhs);
+    drop(builder);
+
+    let err = auto.biunify(lhs_id, rhs_id).unwrap_err();
+    assert_eq!(err.stack.len(), 1);
+}
+
+#[test]
+fn variant() {
+    for &(lhs, rhs, expected) in &[
+        ("[A: bool]", "[A: bool, B: {}]", true),
+        ("[A: bool, B: {}]", "[A: bool]", false),
+        ("[A: bool] | [B: {}]", "[A: bool, B: {}]", true),
+        ("[A: bool] | [B: {}]", "[A: bool]", false),
+        ("[A: bool] | [A: {}]", "[A: bool]", false),
+        ("[A: bool]", "[A: bool, B: {}] & [A: top]", true),
+        ("[A: bool]", "[A: bool] & [B: {}]", false),
+        (
+            "[A: {x: bool, y: bool}] | [A: {x: bool}]",
+            "[A: {x: bool}]",
+            true,
+        ),
+        (
+            "rec a. [A: a, B: bool]",
+            "rec a. [A: a, B: bool, C: {}]",
+            true,
+        ),
+    ] {
+        let con = Constraint(Ty::parse(lhs).unwrap(), Ty::parse(rhs).unwrap());
+        let (mut auto, lhs_id, rhs_id) = build_constraint(&con);
+        assert_eq!(
+            auto.biunify(lhs_id, rhs_id).is_ok(),
+            expected,
+            "{} <= {}",
+            lhs,
+            rhs
+        );
+        assert_eq!(
+            reference::biunify(con).is_ok(),
+            expected,
+            "{} <= {}",
+            lhs,
+            rhs
+        );
+    }
+}
+
+fn build_constraint(con: &Constraint) -> (Automaton<Constructor>, StateId, StateId) {
+    let mut auto = Automaton::new();
+
+    let mut builder = auto.builder();
+    let lhs_id = builder.build_polar(Polarity::Pos, &con.0);
+    let rhs_id = builder.build_polar(Polarity::Neg, &con.1);
+    drop(builder);
+
+    (auto, lhs_id, rhs_id)
+}
+
 proptest! {
     #![proptest_config(Config {
         cases: 1024,
@@ -52,6 +135,21 @@ proptest! {
         );
     }
 
+    #[test]
+    fn biunify_variants(con in arb_variant_constraint()) {
+        let mut auto = Automaton::new();
+
+        let mut builder = auto.builder();
+        let lhs_id = builder.build_polar(Polarity::Pos, &con.0);
+        let rhs_id = builder.build_polar(Polarity::Neg, &con.1);
+        drop(builder);
+
+        prop_assert_eq!(
+            auto.biunify(lhs_id, rhs_id).is_ok(),
+            reference::biunify(con).is_ok()
+        );
+    }
+
     #[test]
     fn biunify_reduced(con in arb_constraint()) {
         let mut auto = Automaton::new();
@@ -69,6 +167,56 @@ proptest! {
             reference::biunify(con).is_ok()
         );
     }
+
+    #[test]
+    #[allow(clippy::result_large_err)]
+    fn biunify_transaction(con in arb_constraint()) {
+        let mut auto = Automaton::new();
+
+        let mut builder = auto.builder();
+        let lhs_id = builder.build_polar(Polarity::Pos, &con.0);
+        let rhs_id = builder.build_polar(Polarity::Neg, &con.1);
+        drop(builder);
+
+        let before = snapshot(&auto);
+        let result = auto.transaction(|auto| auto.biunify(lhs_id, rhs_id));
+        prop_assert_eq!(result.is_ok(), reference::biunify(con).is_ok());
+        if result.is_err() {
+            prop_assert_eq!(snapshot(&auto), before);
+        }
+    }
+
+    #[test]
+    fn biunify_errors(con in arb_constraint()) {
+        let (mut auto, lhs_id, rhs_id) = build_constraint(&con);
+        let errors = auto.biunify_all_errors(once((lhs_id, rhs_id))).err().unwrap_or_default();
+
+        // Both modes check constraints in the same order, so the first error collected is the
+        // one the default mode stops at.
+        let (mut auto, lhs_id, rhs_id) = build_constraint(&con);
+        let error = auto.biunify(lhs_id, rhs_id).err();
+        prop_assert_eq!(errors.is_empty(), error.is_none());
+        prop_assert_eq!(format!("{:?}", errors.first()), format!("{:?}", error.as_ref()));
+
+        prop_assert_eq!(errors.is_empty(), reference::biunify(con).is_ok());
+    }
+
+    #[test]
+    fn biunify_cloned(con in arb_constraint()) {
+        let mut auto = Automaton::new();
+
+        let mut builder = auto.builder();
+        let lhs_id = builder.build_polar(Polarity::Pos, &con.0);
+        let rhs_id = builder.build_polar(Polarity::Neg, &con.1);
+        drop(builder);
+
+        let ids: Vec<_> = auto.clone_states(vec![(lhs_id, Polarity::Pos), (rhs_id, Polarity::Neg)]).collect();
+
+        prop_assert_eq!(
+            auto.biunify(ids[0], ids[1]).is_ok(),
+            reference::biunify(con).is_ok()
+        );
+    }
 }
 
 proptest! {
@@ -117,3 +265,275 @@ proptest! {
         );
     }
 }
+
+#[test]
+fn error_self_required() {
+    // The constraint on the range of the function requires itself after merging.
+    let lhs = Ty::Constructed(Constructed::Fun(
+        Box::new(Ty::Constructed(Constructed::Bool)),
+        Box::new(Ty::Recursive(Box::new(Ty::UnboundVar('b')))),
+    ));
+    let rhs = Ty::Add(
+        Box::new(Ty::Recursive(Box::new(Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::UnboundVar('d')),
+            Box::new(Ty::BoundVar(0)),
+        ))))),
+        Box::new(Ty::Add(
+            Box::new(Ty::UnboundVar('b')),
+            Box::new(Ty::UnboundVar('d')),
+        )),
+    );
+
+    let mut auto = Automaton::new();
+
+    let mut builder = auto.builder();
+    let lhs_id = builder.build_polar(Polarity::Pos, &lhs);
+    let rhs_id = builder.build_polar(Polarity::Neg, &rhs);
+    drop(builder);
+
+    let ids: Vec<_> = auto
+        .clone_states(vec![(lhs_id, Polarity::Pos), (rhs_id, Polarity::Neg)])
+        .collect();
+    assert!(auto.biunify(ids[0], ids[1]).is_err());
+}
+
+fn snapshot(auto: &Automaton<Constructor>) -> (String, Vec<(StateId, StateId)>) {
+    let mut cache: Vec<_> = auto.biunify_cache.keys().copied().collect();
+    cache.sort();
+    (format!("{:?}", auto.states), cache)
+}
+
+#[test]
+#[allow(clippy::result_large_err)]
+fn transaction_rollback() {
+    let mut auto = Automaton::new();
+
+    let mut builder = auto.builder();
+    let lhs_id = builder.build_polar(
+        Polarity::Pos,
+        &Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::UnboundVar('a')),
+            Box::new(Ty::Constructed(Constructed::Bool)),
+        )),
+    );
+    let rhs_id = builder.build_polar(
+        Polarity::Neg,
+        &Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::Constructed(Constructed::Bool)),
+            Box::new(Ty::Constructed(Constructed::Record(Default::default()))),
+        )),
+    );
+    drop(builder);
+
+    let before = snapshot(&auto);
+    assert!(auto
+        .transaction(|auto| auto.biunify(lhs_id, rhs_id))
+        .is_err());
+    assert_eq!(snapshot(&auto), before);
+
+    // The automaton can still be used after the failed transaction.
+    let rhs_id = auto.builder::<char>().build_polar(
+        Polarity::Neg,
+        &Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::Constructed(Constructed::Bool)),
+            Box::new(Ty::Constructed(Constructed::Bool)),
+        )),
+    );
+    assert!(auto
+        .transaction(|auto| auto.biunify(lhs_id, rhs_id))
+        .is_ok());
+    assert!(auto.biunify_cache.contains_key(&(lhs_id, rhs_id)));
+}
+
+#[test]
+#[allow(clippy::result_large_err)]
+fn nested_transaction_rollback() {
+    let mut auto = Automaton::new();
+
+    let mut builder = auto.builder();
+    let lhs_id = builder.build_polar(
+        Polarity::Pos,
+        &Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::UnboundVar('a')),
+            Box::new(Ty::UnboundVar('a')),
+        )),
+    );
+    drop(builder);
+
+    let before = snapshot(&auto);
+    let result = auto.transaction(|auto| {
+        // The inner transaction succeeds, but its changes are still undone along with the
+        // outer one.
+        let rhs_id = auto.builder::<char>().build_polar(
+            Polarity::Neg,
+            &Ty::Constructed(Constructed::Fun(
+                Box::new(Ty::Constructed(Constructed::Bool)),
+                Box::new(Ty::UnboundVar('b')),
+            )),
+        );
+        auto.transaction(|auto| auto.biunify(lhs_id, rhs_id))?;
+        assert_ne!(snapshot(auto), before);
+
+        let rhs_id = auto.builder::<char>().build_polar(
+            Polarity::Neg,
+            &Ty::Constructed(Constructed::Fun(
+                Box::new(Ty::UnboundVar('c')),
+                Box::new(Ty::Constructed(Constructed::Record(Default::default()))),
+            )),
+        );
+        auto.transaction(|auto| auto.biunify(lhs_id, rhs_id))
+    });
+    assert!(result.is_err());
+    assert_eq!(snapshot(&auto), before);
+}
+
+#[test]
+fn collect_errors() {
+    let mut auto = Automaton::new();
+
+    let mut builder = auto.builder::<char>();
+    let lhs_id = builder.build_polar(
+        Polarity::Pos,
+        &Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::Constructed(Constructed::Bool)),
+            Box::new(Ty::Constructed(Constructed::Record(Default::default()))),
+        )),
+    );
+    let rhs_id = builder.build_polar(
+        Polarity::Neg,
+        &Ty::Constructed(Constructed::Fun(
+            Box::new(Ty::Constructed(Constructed::Record(Default::default()))),
+            Box::new(Ty::Constructed(Constructed::Bool)),
+        )),
+    );
+    drop(builder);
+
+    let errors = auto.biunify_all_errors(once((lhs_id, rhs_id))).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|error| error.stack.len() == 1));
+}
+
+#[test]
+fn fail_fast() {
+    // bool | 'a ≤ {} & 'b
+    let mut auto = Automaton::new();
+
+    let mut builder = auto.builder();
+    let lhs_id = builder.build_polar(
+        Polarity::Pos,
+        &Ty::Add(
+            Box::new(Ty::Constructed(Constructed::Bool)),
+            Box::new(Ty::UnboundVar('a')),
+        ),
+    );
+    let rhs_id = builder.build_polar(
+        Polarity::Neg,
+        &Ty::Add(
+            Box::new(Ty::Constructed(Constructed::Record(Default::default()))),
+            Box::new(Ty::UnboundVar('b')),
+        ),
+    );
+    drop(builder);
+
+    // The first error is returned before the flow edges of the failing constraint are merged.
+    let before = format!("{:?}", auto.states);
+    assert!(auto.biunify(lhs_id, rhs_id).is_err());
+    assert_eq!(format!("{:?}", auto.states), before);
+}
+
+#[test]
+fn error_provenance() {
+    let mut auto = Automaton::new();
+    let mut provenance = Provenance::new();
+
+    // {x: bool}, where the field was built separately
+    let lhs_id = provenance.record(&mut auto, "lhs", |auto, provenance| {
+        let field = provenance.record(auto, "field", |auto, _| {
+            auto.build_constructed(Polarity::Pos, Constructor::Bool)
+        });
+        let fields = once((Rc::from("x"), StateSet::new(field))).collect();
+        auto.build_constructed(Polarity::Pos, Constructor::Record(fields))
+    });
+    // {x: ⊤ -> ⊥}
+    let rhs_id = provenance.record(&mut auto, "rhs", |auto, _| {
+        let mut fields = BTreeMap::new();
+        fields.insert(
+            Rc::from("x"),
+            Box::new(Ty::Constructed(Constructed::Fun(
+                Box::new(Ty::Zero),
+                Box::new(Ty::Zero),
+            ))),
+        );
+        auto.builder::<char>()
+            .build_polar(Polarity::Neg, &Ty::Constructed(Constructed::Record(fields)))
+    });
+
+    let error = auto.biunify(lhs_id, rhs_id).unwrap_err();
+    assert_eq!(error.stack.len(), 1);
+    assert_eq!(error.root(), (lhs_id, rhs_id));
+
+    let (pos, neg) = error.states().next().unwrap();
+    assert_eq!(error.constraint.0.id, pos);
+    assert_eq!(error.constraint.1.id, neg);
+
+    assert_eq!(provenance.get(lhs_id), Some(&"lhs"));
+    assert_eq!(
+        error.provenance(&provenance),
+        (Some(&"field"), Some(&"rhs"))
+    );
+}
+
+#[test]
+fn error_display() {
+    let record = |ty| {
+        let mut fields = BTreeMap::new();
+        fields.insert(Rc::from("x"), Box::new(ty));
+        Ty::Constructed(Constructed::Record(fields))
+    };
+    let fun = |d, r| Ty::Constructed(Constructed::Fun(Box::new(d), Box::new(r)));
+
+    let mut auto = Automaton::new();
+
+    let mut builder = auto.builder::<char>();
+    let lhs_id = builder.build_polar(
+        Polarity::Pos,
+        &fun(
+            record(Ty::Constructed(Constructed::Bool)),
+            Ty::Constructed(Constructed::Bool),
+        ),
+    );
+    let rhs_id = builder.build_polar(
+        Polarity::Neg,
+        &fun(record(record(Ty::Zero)), Ty::Constructed(Constructed::Bool)),
+    );
+    drop(builder);
+
+    let error = auto.biunify(lhs_id, rhs_id).unwrap_err();
+    let display = error.display(
+        |con, f| match con {
+            Constructor::Bool => write!(f, "bool"),
+            Constructor::Fun(..) => write!(f, "a function"),
+            Constructor::Record(fields) => {
+                write!(f, "{{")?;
+                for (idx, field) in fields.keys().enumerate() {
+                    if idx != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ...", field)?;
+                }
+                write!(f, "}}")
+            }
+            Constructor::Variant(_) => write!(f, "a variant"),
+        },
+        |label, f| match label {
+            Label::Domain => write!(f, "the argument"),
+            Label::Range => write!(f, "the result"),
+            Label::Label(field) => write!(f, "field `{}`", field),
+            Label::Tag(tag) => write!(f, "tag `{}`", tag),
+        },
+    );
+    assert_eq!(
+        display.to_string(),
+        "expected bool, found {x: ...} in field `x` of the argument"
+    );
+}
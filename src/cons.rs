@@ -1,10 +1,11 @@
 use std::borrow::Cow;
+use std::convert::Infallible;
 use std::fmt::{self, Debug};
 
 use itertools::{merge_join_by, EitherOrBoth};
 use small_ord_set::{self, KeyValuePair, SmallOrdSet};
 
-use crate::auto::StateSet;
+use crate::auto::{StateId, StateSet};
 use crate::Polarity;
 
 pub trait Constructor: Clone + PartialOrd {
@@ -19,11 +20,28 @@ pub trait Constructor: Clone + PartialOrd {
     where
         F: FnMut(Self::Label, &StateSet, &StateSet) -> Result<(), E>;
 
+    /// Visit the type parameters of this constructor.
+    fn visit_params<F>(&self, mut visit: F)
+    where
+        F: FnMut(Self::Label, &StateSet),
+    {
+        self.visit_params_intersection::<_, Infallible>(self, |label, set, _| {
+            visit(label, set);
+            Ok(())
+        })
+        .unwrap();
+    }
+
     fn map<F>(self, mapper: F) -> Self
     where
         F: FnMut(Self::Label, StateSet) -> StateSet;
 }
 
+/// The label of a type parameter of a [`Constructor`].
+///
+/// Each parameter is either covariant or contravariant. An invariant parameter, such as the
+/// type of a mutable reference, is represented by a pair of parameters: a covariant one for the
+/// values read and a contravariant one for the values written.
 pub trait Label: Clone {
     fn polarity(&self) -> Polarity;
 }
@@ -78,6 +96,21 @@ impl<C: Constructor> ConstructorSet<C> {
             .collect();
         ConstructorSet { set }
     }
+
+    pub(crate) fn remap<F>(self, mut mapper: F) -> Self
+    where
+        F: FnMut(StateId) -> StateId,
+    {
+        let set = self
+            .set
+            .into_iter()
+            .map(|kvp| KeyValuePair {
+                key: kvp.key,
+                value: kvp.value.map(|_, set| set.remap(&mut mapper)),
+            })
+            .collect();
+        ConstructorSet { set }
+    }
 }
 
 impl<C: Debug + Constructor> Debug for ConstructorSet<C> {
@@ -0,0 +1,316 @@
+//! Type inference for a small ML-like expression language, using the constructors in
+//! [`std_cons`](crate::std_cons).
+//!
+//! This follows the type system of MLsub: each expression is given a [`Scheme`] recording the
+//! types it requires of the λ-bound variables it uses, and these are combined with meets as
+//! the expression is checked. It serves as an example of building and solving constraints with
+//! an [`Automaton`], and as a reference implementation to test other frontends against.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::once;
+use std::rc::Rc;
+
+use crate::auto::{Automaton, Scheme, StateId, StateSet};
+use crate::polar::Ty;
+use crate::std_cons::{Constructed, Constructor};
+use crate::{BiunifyError, Polarity};
+
+/// An expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Lit(Lit),
+    Var(Rc<str>),
+    /// A function `fun x -> e`.
+    Lambda(Rc<str>, Box<Expr>),
+    /// A function application `e1 e2`.
+    App(Box<Expr>, Box<Expr>),
+    /// A polymorphic binding `let x = e1 in e2`.
+    Let(Rc<str>, Box<Expr>, Box<Expr>),
+    /// A record `{x = e1, y = e2}`.
+    Record(BTreeMap<Rc<str>, Expr>),
+    /// A field projection `e.x`.
+    Proj(Box<Expr>, Rc<str>),
+    /// A conditional `if e1 then e2 else e3`.
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+/// A literal, of type `bool` or `int`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Lit {
+    Bool(bool),
+    Int(i64),
+}
+
+/// An error encountered while inferring the type of an expression.
+#[derive(Debug)]
+pub enum Error {
+    /// A variable was used outside of the scope of any binding.
+    Unbound(Rc<str>),
+    /// The expression is not well-typed.
+    Biunify(Box<BiunifyError<Constructor>>),
+}
+
+/// Infers principal types for expressions.
+///
+/// Variables bound by [`define`](Infer::define) are available in every later expression, like
+/// top-level `let` bindings.
+pub struct Infer {
+    auto: Automaton<Constructor>,
+    scope: Scope,
+}
+
+// The variables in scope, innermost last.
+#[derive(Default)]
+struct Scope {
+    vars: Vec<(Rc<str>, Binding)>,
+}
+
+enum Binding {
+    // A λ-bound variable, whose type is given by the environment of the expression using it.
+    Mono,
+    // A let-bound variable, which is instantiated at each use.
+    Poly(Scheme<Rc<str>>),
+}
+
+/// Infers the type of a closed expression.
+pub fn infer(expr: &Expr) -> Result<Ty<Constructed<usize>, usize>, Error> {
+    let mut infer = Infer::new();
+    let scheme = infer.infer(expr)?;
+    Ok(infer.to_polar_ty(scheme.ty))
+}
+
+impl Infer {
+    pub fn new() -> Self {
+        Infer {
+            auto: Automaton::new(),
+            scope: Scope::default(),
+        }
+    }
+
+    pub fn automaton(&self) -> &Automaton<Constructor> {
+        &self.auto
+    }
+
+    pub fn automaton_mut(&mut self) -> &mut Automaton<Constructor> {
+        &mut self.auto
+    }
+
+    /// Infers the typing of an expression. The environment of the returned scheme is empty, since
+    /// there are no λ-bound variables at the top level.
+    ///
+    /// If the expression is not well-typed, the automaton is left unchanged.
+    pub fn infer(&mut self, expr: &Expr) -> Result<Scheme<Rc<str>>, Error> {
+        let scope = &mut self.scope;
+        self.auto.transaction(|auto| scope.infer(auto, expr))
+    }
+
+    /// Infers the typing of an expression and passes it to `f`, then discards the states added
+    /// while inferring it, so the automaton is left unchanged.
+    pub fn infer_scratch<T, F>(&mut self, expr: &Expr, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&Automaton<Constructor>, Scheme<Rc<str>>) -> T,
+    {
+        let scope = &mut self.scope;
+        let mut output = None;
+        // The transaction always fails, with no error if the expression was well-typed.
+        let result = self.auto.transaction(|auto| {
+            let scheme = scope.infer(auto, expr).map_err(Some)?;
+            output = Some(f(auto, scheme));
+            Err(None)
+        });
+        match result {
+            Ok(()) | Err(None) => Ok(output.unwrap()),
+            Err(Some(err)) => Err(err),
+        }
+    }
+
+    /// Infers the typing of an expression, and binds `name` to it in later expressions.
+    pub fn define(&mut self, name: Rc<str>, expr: &Expr) -> Result<Scheme<Rc<str>>, Error> {
+        let scheme = self.infer(expr)?.generalize(&mut self.auto);
+        self.scope.vars.push((name, Binding::Poly(scheme.clone())));
+        Ok(scheme)
+    }
+
+    /// Gets the simplified type of a positive state.
+    ///
+    /// The type is simplified in a separate automaton, so no states are added to this one.
+    pub fn to_polar_ty(&self, ty: StateId) -> Ty<Constructed<usize>, usize> {
+        let mut reduced = Automaton::new();
+        let id = reduced
+            .reduce(&self.auto, once((ty, Polarity::Pos)))
+            .next()
+            .unwrap();
+
+        let mut minimized = Automaton::new();
+        let id = minimized
+            .minimize(&reduced, once((id, Polarity::Pos)))
+            .next()
+            .unwrap();
+        minimized.to_polar_ty(id, Polarity::Pos)
+    }
+}
+
+impl Default for Infer {
+    fn default() -> Self {
+        Infer::new()
+    }
+}
+
+impl Scope {
+    fn infer(
+        &mut self,
+        auto: &mut Automaton<Constructor>,
+        expr: &Expr,
+    ) -> Result<Scheme<Rc<str>>, Error> {
+        match expr {
+            Expr::Lit(lit) => {
+                let name = match lit {
+                    Lit::Bool(_) => "bool",
+                    Lit::Int(_) => "int",
+                };
+                let ty = auto.build_constructed(Polarity::Pos, Constructor::Base(Rc::from(name)));
+                Ok(Scheme::new(BTreeMap::new(), ty))
+            }
+            Expr::Var(name) => match self.vars.iter().rev().find(|(var, _)| var == name) {
+                Some((_, Binding::Poly(scheme))) => Ok(scheme.instantiate(auto)),
+                Some((_, Binding::Mono)) => {
+                    // Each use of a λ-bound variable gets a fresh type, and the uses are
+                    // combined when the environments are met.
+                    let var = auto.build_var();
+                    Ok(Scheme::new(
+                        once((name.clone(), var.neg)).collect(),
+                        var.pos,
+                    ))
+                }
+                None => Err(Error::Unbound(name.clone())),
+            },
+            Expr::Lambda(param, body) => {
+                self.vars.push((param.clone(), Binding::Mono));
+                let body = self.infer(auto, body);
+                self.vars.pop();
+
+                let mut body = body?;
+                let domain = match body.env.remove(param) {
+                    Some(domain) => domain,
+                    None => auto.build_empty(Polarity::Neg),
+                };
+                let ty = auto.build_constructed(
+                    Polarity::Pos,
+                    Constructor::Fun(StateSet::new(domain), StateSet::new(body.ty)),
+                );
+                Ok(Scheme::new(body.env, ty))
+            }
+            Expr::App(fun, arg) => {
+                let fun = self.infer(auto, fun)?;
+                let arg = self.infer(auto, arg)?;
+
+                let result = auto.build_var();
+                let expected = auto.build_constructed(
+                    Polarity::Neg,
+                    Constructor::Fun(StateSet::new(arg.ty), StateSet::new(result.neg)),
+                );
+                auto.biunify(fun.ty, expected)?;
+                Ok(Scheme::new(
+                    meet_envs(auto, vec![fun.env, arg.env]),
+                    result.pos,
+                ))
+            }
+            Expr::Let(name, bound, body) => {
+                let bound = self.infer(auto, bound)?;
+
+                self.vars
+                    .push((name.clone(), Binding::Poly(bound.generalize(auto))));
+                let body = self.infer(auto, body);
+                self.vars.pop();
+
+                // The requirements of the bound expression apply even if it is never used.
+                let body = body?;
+                Ok(Scheme::new(
+                    meet_envs(auto, vec![bound.env, body.env]),
+                    body.ty,
+                ))
+            }
+            Expr::Record(fields) => {
+                let mut envs = Vec::with_capacity(fields.len());
+                let mut tys = im::OrdMap::new();
+                for (label, field) in fields {
+                    let field = self.infer(auto, field)?;
+                    envs.push(field.env);
+                    tys.insert(label.clone(), StateSet::new(field.ty));
+                }
+
+                let ty = auto.build_constructed(Polarity::Pos, Constructor::Record(tys));
+                Ok(Scheme::new(meet_envs(auto, envs), ty))
+            }
+            Expr::Proj(record, label) => {
+                let record = self.infer(auto, record)?;
+
+                let result = auto.build_var();
+                let expected = auto.build_constructed(
+                    Polarity::Neg,
+                    Constructor::Record(once((label.clone(), StateSet::new(result.neg))).collect()),
+                );
+                auto.biunify(record.ty, expected)?;
+                Ok(Scheme::new(record.env, result.pos))
+            }
+            Expr::If(cond, then, els) => {
+                let cond = self.infer(auto, cond)?;
+                let then = self.infer(auto, then)?;
+                let els = self.infer(auto, els)?;
+
+                let expected =
+                    auto.build_constructed(Polarity::Neg, Constructor::Base(Rc::from("bool")));
+                auto.biunify(cond.ty, expected)?;
+                let ty = auto.build_add(Polarity::Pos, vec![then.ty, els.ty]);
+                Ok(Scheme::new(
+                    meet_envs(auto, vec![cond.env, then.env, els.env]),
+                    ty,
+                ))
+            }
+        }
+    }
+}
+
+// Combines the environments of subexpressions, so a variable used by more than one of them is
+// required to have the meet of their types.
+fn meet_envs(
+    auto: &mut Automaton<Constructor>,
+    envs: Vec<BTreeMap<Rc<str>, StateId>>,
+) -> BTreeMap<Rc<str>, StateId> {
+    let mut result = BTreeMap::new();
+    for (name, id) in envs.into_iter().flatten() {
+        let id = match result.remove(&name) {
+            Some(prev) => auto.build_add(Polarity::Neg, vec![prev, id]),
+            None => id,
+        };
+        result.insert(name, id);
+    }
+    result
+}
+
+impl From<BiunifyError<Constructor>> for Error {
+    fn from(err: BiunifyError<Constructor>) -> Self {
+        Error::Biunify(Box::new(err))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unbound(name) => write!(f, "unbound variable `{}`", name),
+            Error::Biunify(err) => {
+                let display = err.display(
+                    |con, f| write!(f, "{}", con),
+                    |label, f| write!(f, "{}", label),
+                );
+                write!(f, "{}", display)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,214 @@
+use std::rc::Rc;
+
+use crate::infer::{infer, Error, Expr, Infer, Lit};
+use crate::polar::Ty;
+use crate::std_cons::Constructed;
+use crate::Polarity;
+
+fn int(n: i64) -> Expr {
+    Expr::Lit(Lit::Int(n))
+}
+
+fn bool(b: bool) -> Expr {
+    Expr::Lit(Lit::Bool(b))
+}
+
+fn var(name: &str) -> Expr {
+    Expr::Var(Rc::from(name))
+}
+
+fn lambda(param: &str, body: Expr) -> Expr {
+    Expr::Lambda(Rc::from(param), Box::new(body))
+}
+
+fn app(fun: Expr, arg: Expr) -> Expr {
+    Expr::App(Box::new(fun), Box::new(arg))
+}
+
+fn let_(name: &str, bound: Expr, body: Expr) -> Expr {
+    Expr::Let(Rc::from(name), Box::new(bound), Box::new(body))
+}
+
+fn record(fields: Vec<(&str, Expr)>) -> Expr {
+    Expr::Record(
+        fields
+            .into_iter()
+            .map(|(label, expr)| (Rc::from(label), expr))
+            .collect(),
+    )
+}
+
+fn proj(record: Expr, label: &str) -> Expr {
+    Expr::Proj(Box::new(record), Rc::from(label))
+}
+
+fn if_(cond: Expr, then: Expr, els: Expr) -> Expr {
+    Expr::If(Box::new(cond), Box::new(then), Box::new(els))
+}
+
+fn check(expr: Expr) -> String {
+    match infer(&expr) {
+        Ok(ty) => ty.display(Polarity::Pos).to_string(),
+        Err(err) => panic!("{}", err),
+    }
+}
+
+fn check_err(expr: Expr) -> String {
+    infer(&expr).unwrap_err().to_string()
+}
+
+#[test]
+fn literals() {
+    assert_eq!(check(int(1)), "int");
+    assert_eq!(check(bool(true)), "bool");
+}
+
+#[test]
+fn lambda_app() {
+    assert_eq!(check(lambda("x", var("x"))), "'a -> 'a");
+    assert_eq!(check(lambda("x", int(1))), "⊤ -> int");
+    assert_eq!(check(app(lambda("x", var("x")), int(1))), "int");
+    assert_eq!(
+        check(lambda(
+            "f",
+            lambda("x", app(var("f"), app(var("f"), var("x"))))
+        )),
+        "(('a ⊔ 'b) -> 'b) -> 'a -> 'b"
+    );
+    assert_eq!(
+        check(lambda("x", app(var("x"), var("x")))),
+        "('a ⊓ 'a -> 'b) -> 'b"
+    );
+}
+
+#[test]
+fn let_polymorphism() {
+    let uses = |id| {
+        record(vec![
+            ("a", app(var(id), int(1))),
+            ("b", app(var(id), bool(true))),
+        ])
+    };
+    assert_eq!(
+        check(let_("id", lambda("x", var("x")), uses("id"))),
+        "{a: int, b: bool}"
+    );
+    // A λ-bound variable is monomorphic, so both uses have the same type.
+    assert_eq!(
+        check(lambda("id", uses("id"))),
+        "((bool ⊔ int) -> 'a) -> {a: 'a, b: 'a}"
+    );
+}
+
+#[test]
+fn records() {
+    assert_eq!(
+        check(record(vec![("x", int(1)), ("y", bool(false))])),
+        "{x: int, y: bool}"
+    );
+    assert_eq!(check(lambda("r", proj(var("r"), "x"))), "{x: 'a} -> 'a");
+    assert_eq!(
+        check(lambda(
+            "r",
+            record(vec![("x", proj(var("r"), "x")), ("y", proj(var("r"), "y"))])
+        )),
+        "{x: 'a, y: 'b} -> {x: 'a, y: 'b}"
+    );
+}
+
+#[test]
+fn conditionals() {
+    assert_eq!(
+        check(lambda("b", if_(var("b"), int(1), bool(true)))),
+        "bool -> (bool ⊔ int)"
+    );
+    assert_eq!(
+        check(lambda(
+            "x",
+            lambda("y", if_(bool(true), var("x"), var("y")))
+        )),
+        "'a -> 'a -> 'a"
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(check_err(var("x")), "unbound variable `x`");
+    assert_eq!(
+        check_err(app(int(1), int(2))),
+        "expected ... -> ..., found int"
+    );
+    assert_eq!(
+        check_err(if_(int(1), int(2), int(3))),
+        "expected bool, found int"
+    );
+    assert_eq!(
+        check_err(proj(record(vec![("x", int(1))]), "y")),
+        "expected {y: ...}, found {x: ...}"
+    );
+    assert_eq!(
+        check_err(app(
+            lambda("f", app(var("f"), int(1))),
+            lambda("b", if_(var("b"), int(1), int(2)))
+        )),
+        "expected bool, found int in the argument of the argument"
+    );
+}
+
+#[test]
+fn define() {
+    let mut infer = Infer::new();
+    infer
+        .define(Rc::from("id"), &lambda("x", var("x")))
+        .unwrap();
+    assert!(matches!(
+        infer.infer(&app(var("id"), var("y"))),
+        Err(Error::Unbound(_))
+    ));
+    assert!(matches!(
+        infer.infer(&app(int(1), var("id"))),
+        Err(Error::Biunify(_))
+    ));
+
+    let scheme = infer
+        .infer(&record(vec![
+            ("a", app(var("id"), int(1))),
+            ("b", app(var("id"), bool(true))),
+        ]))
+        .unwrap();
+    assert!(scheme.env.is_empty());
+
+    // Printing a type does not add states to the session.
+    let len = infer.automaton().states.len();
+    assert_eq!(
+        infer
+            .to_polar_ty(scheme.ty)
+            .display(Polarity::Pos)
+            .to_string(),
+        "{a: int, b: bool}"
+    );
+    assert_eq!(infer.automaton().states.len(), len);
+}
+
+#[test]
+fn infer_scratch() {
+    let mut infer = Infer::new();
+    infer
+        .define(Rc::from("id"), &lambda("x", var("x")))
+        .unwrap();
+
+    let len = infer.automaton().states.len();
+    let ty: Ty<Constructed<usize>, usize> = infer
+        .infer_scratch(&app(var("id"), int(1)), |auto, scheme| {
+            auto.to_polar_ty(scheme.ty, Polarity::Pos)
+        })
+        .unwrap();
+    assert_eq!(ty.display(Polarity::Pos).to_string(), "int");
+    assert_eq!(infer.automaton().states.len(), len);
+
+    assert!(matches!(
+        infer.infer_scratch(&app(int(1), var("id")), |_, _| ()),
+        Err(Error::Biunify(_))
+    ));
+    assert_eq!(infer.automaton().states.len(), len);
+}
@@ -1,18 +1,27 @@
+#[cfg(any(test, feature = "arbitrary"))]
+pub mod arbitrary;
 pub mod auto;
 pub mod cons;
+#[cfg(feature = "infer")]
+pub mod infer;
 pub mod polar;
+#[cfg(feature = "std_cons")]
+pub mod std_cons;
 
 mod biunify;
 mod subsume;
 #[cfg(test)]
 mod tests;
 
-pub use self::biunify::{Error as BiunifyError, Result as BiunifyResult};
+pub use self::biunify::{Error as BiunifyError, Result as BiunifyResult, Side as BiunifySide};
 pub use self::cons::{Constructor, ConstructorSet, Label};
+#[cfg(feature = "derive")]
+pub use mlsub_derive::Constructor;
 
 use std::ops;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Polarity {
     Neg = -1,
     Pos = 1,
@@ -0,0 +1,18 @@
+mod parse;
+mod print;
+mod validate;
+
+pub use self::parse::{Parse, ParseError, Parser};
+pub use self::print::{print_fields, Display, Precedence, Print};
+pub use self::validate::ValidateError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ty<C, V> {
+    Zero,
+    Add(Box<Ty<C, V>>, Box<Ty<C, V>>),
+    UnboundVar(V),
+    BoundVar(usize),
+    Constructed(C),
+    Recursive(Box<Ty<C, V>>),
+}
@@ -0,0 +1,283 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::polar::{Precedence, Ty};
+
+/// Parsing of the type constructors in a [`Ty`].
+pub trait Parse<V>: Sized {
+    /// Tries to parse a constructor beginning with a keyword or delimiter, such as `bool` or
+    /// `{x: t}`. Returns `None` without consuming any input if there is no such constructor.
+    fn parse_atom(parser: &mut Parser<'_, Self, V>) -> Result<Option<Self>, ParseError>;
+
+    /// Tries to parse an infix constructor such as `t -> u`, given its left operand. Returns the
+    /// operand without consuming any input if there is no such constructor.
+    fn parse_infix(
+        lhs: Ty<Self, V>,
+        _parser: &mut Parser<'_, Self, V>,
+    ) -> Result<Result<Self, Ty<Self, V>>, ParseError> {
+        Ok(Err(lhs))
+    }
+
+    /// Tries to create a tuple constructor from a parenthesized list of types such as `(t, u)`
+    /// or `()`. Returns `None` if tuples are not supported.
+    fn parse_tuple(_elems: Vec<Ty<Self, V>>) -> Option<Self> {
+        None
+    }
+}
+
+/// An error from parsing a [`Ty`], with the byte range of the input it occurred at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+/// The state of a parser, passed to [`Parse`] implementations to parse constructors.
+pub struct Parser<'a, B, V> {
+    input: &'a str,
+    pos: usize,
+    // Names bound by enclosing recursive types, innermost last.
+    binders: Vec<&'a str>,
+    marker: PhantomData<fn() -> Ty<B, V>>,
+}
+
+impl<B, V> Ty<B, V>
+where
+    B: Parse<V>,
+    V: FromStr,
+{
+    /// Parses a type such as `rec a. bool -> a | {x: 'b}`.
+    ///
+    /// Names bound by `rec` become [`BoundVar`](Ty::BoundVar)s, and free names, which are
+    /// prefixed by `'`, become [`UnboundVar`](Ty::UnboundVar)s. Joins and meets may be written
+    /// as `⊔`, `⊓`, `|` or `&`, and empty types as `⊥`, `⊤`, `bot` or `top`.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            input,
+            pos: 0,
+            binders: Vec::new(),
+            marker: PhantomData,
+        };
+        let ty = parser.parse(Precedence::Recursive)?;
+        parser.skip_whitespace();
+        if parser.pos == input.len() {
+            Ok(ty)
+        } else {
+            Err(parser.error("expected end of input"))
+        }
+    }
+}
+
+impl<'a, B, V> Parser<'a, B, V>
+where
+    B: Parse<V>,
+    V: FromStr,
+{
+    /// Parses a type which binds at least as tightly as `prec`.
+    pub fn parse(&mut self, prec: Precedence) -> Result<Ty<B, V>, ParseError> {
+        match prec {
+            Precedence::Recursive | Precedence::Add => self.parse_add(),
+            Precedence::Operator => self.parse_operator(),
+            Precedence::Atom => self.parse_atom(),
+        }
+    }
+
+    /// Parses fields such as `x: t, y: u` up to and including the `close` token, for
+    /// constructors like records which map labels to type parameters.
+    pub fn parse_fields<T>(&mut self, close: &str) -> Result<BTreeMap<Rc<str>, T>, ParseError>
+    where
+        T: From<Ty<B, V>>,
+    {
+        let mut fields = BTreeMap::new();
+        if !self.eat(close) {
+            loop {
+                let label = self.expect_ident()?;
+                self.expect(":")?;
+                let ty = self.parse(Precedence::Recursive)?;
+                fields.insert(Rc::from(label), T::from(ty));
+                if self.eat(close) {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+        Ok(fields)
+    }
+
+    fn parse_add(&mut self) -> Result<Ty<B, V>, ParseError> {
+        let mut ty = self.parse_operator()?;
+        while self.eat("⊔") || self.eat("⊓") || self.eat("|") || self.eat("&") {
+            let rhs = self.parse_operator()?;
+            ty = Ty::Add(Box::new(ty), Box::new(rhs));
+        }
+        Ok(ty)
+    }
+
+    fn parse_operator(&mut self) -> Result<Ty<B, V>, ParseError> {
+        let mut ty = self.parse_atom()?;
+        loop {
+            match B::parse_infix(ty, self)? {
+                Ok(con) => ty = Ty::Constructed(con),
+                Err(lhs) => return Ok(lhs),
+            }
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Ty<B, V>, ParseError> {
+        if self.eat("(") {
+            let start = self.pos - 1;
+            let mut elems = Vec::new();
+            if !self.eat(")") {
+                loop {
+                    elems.push(self.parse(Precedence::Recursive)?);
+                    if self.eat(")") {
+                        // Without a trailing comma, a single type is only parenthesized.
+                        if elems.len() == 1 {
+                            return Ok(elems.pop().unwrap());
+                        }
+                        break;
+                    }
+                    if !self.eat(",") {
+                        return Err(self.error("expected `)`"));
+                    }
+                    if self.eat(")") {
+                        break;
+                    }
+                }
+            }
+            match B::parse_tuple(elems) {
+                Some(con) => Ok(Ty::Constructed(con)),
+                None => Err(ParseError {
+                    span: start..self.pos,
+                    message: "tuple types are not supported".to_owned(),
+                }),
+            }
+        } else if self.eat("rec") {
+            // A recursive type extends as far to the right as possible.
+            let name = self.expect_ident()?;
+            self.expect(".")?;
+            self.binders.push(name);
+            let ty = self.parse(Precedence::Recursive);
+            self.binders.pop();
+            Ok(Ty::Recursive(Box::new(ty?)))
+        } else if self.eat("⊥") || self.eat("⊤") || self.eat("bot") || self.eat("top") {
+            Ok(Ty::Zero)
+        } else if self.eat("'") {
+            let start = self.pos;
+            let name = self.expect_ident()?;
+            match name.parse() {
+                Ok(var) => Ok(Ty::UnboundVar(var)),
+                Err(_) => Err(ParseError {
+                    span: start..self.pos,
+                    message: format!("invalid type variable `'{}`", name),
+                }),
+            }
+        } else if let Some(idx) = self.peek_binder() {
+            self.ident();
+            Ok(Ty::BoundVar(idx))
+        } else if let Some(con) = B::parse_atom(self)? {
+            Ok(Ty::Constructed(con))
+        } else {
+            Err(self.error("expected a type"))
+        }
+    }
+
+    fn peek_binder(&mut self) -> Option<usize> {
+        let pos = self.pos;
+        let name = self.ident();
+        self.pos = pos;
+        let name = name?;
+        self.binders.iter().rev().position(|&binder| binder == name)
+    }
+}
+
+impl<'a, B, V> Parser<'a, B, V> {
+    /// Consumes `token` if it is next in the input. Keywords only match whole identifiers.
+    pub fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        if !rest.starts_with(token) {
+            return false;
+        }
+        if token.chars().all(is_ident_char) && rest[token.len()..].starts_with(is_ident_char) {
+            return false;
+        }
+        self.pos += token.len();
+        true
+    }
+
+    /// Consumes `token`, or returns an error if it is not next in the input.
+    pub fn expect(&mut self, token: &str) -> Result<(), ParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", token)))
+        }
+    }
+
+    /// Consumes an identifier, if one is next in the input.
+    pub fn ident(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        if !rest.starts_with(|ch: char| ch.is_alphabetic() || ch == '_') {
+            return None;
+        }
+        let len = rest.find(|ch| !is_ident_char(ch)).unwrap_or(rest.len());
+        self.pos += len;
+        Some(&rest[..len])
+    }
+
+    /// Consumes an identifier, or returns an error if one is not next in the input.
+    pub fn expect_ident(&mut self) -> Result<&'a str, ParseError> {
+        match self.ident() {
+            Some(ident) => Ok(ident),
+            None => Err(self.error("expected an identifier")),
+        }
+    }
+
+    /// Creates an error at the next token in the input.
+    pub fn error(&mut self, message: &str) -> ParseError {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = match rest.chars().next() {
+            Some(ch) if is_ident_char(ch) => {
+                rest.find(|ch| !is_ident_char(ch)).unwrap_or(rest.len())
+            }
+            Some(ch) => ch.len_utf8(),
+            None => 0,
+        };
+        ParseError {
+            span: self.pos..(self.pos + len),
+            message: message.to_owned(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+}
+
+fn is_ident_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl Error for ParseError {}
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use proptest::test_runner::Config;
+use proptest::{prop_assert_eq, proptest};
+
+use crate::polar::{ParseError, Ty};
+use crate::tests::{arb_polar_ty, Constructed};
+use crate::Polarity;
+
+fn parse(input: &str) -> Result<Ty<Constructed, char>, ParseError> {
+    Ty::parse(input)
+}
+
+fn bool() -> Ty<Constructed, char> {
+    Ty::Constructed(Constructed::Bool)
+}
+
+fn fun(d: Ty<Constructed, char>, r: Ty<Constructed, char>) -> Ty<Constructed, char> {
+    Ty::Constructed(Constructed::Fun(Box::new(d), Box::new(r)))
+}
+
+fn error(span: std::ops::Range<usize>, message: &str) -> Result<Ty<Constructed, char>, ParseError> {
+    Err(ParseError {
+        span,
+        message: message.to_owned(),
+    })
+}
+
+#[test]
+fn parse_example() {
+    let mut fields = BTreeMap::new();
+    fields.insert(Rc::from("x"), Box::new(Ty::UnboundVar('b')));
+
+    assert_eq!(
+        parse("rec a. bool -> a | {x: 'b}"),
+        Ok(Ty::Recursive(Box::new(Ty::Add(
+            Box::new(fun(bool(), Ty::BoundVar(0))),
+            Box::new(Ty::Constructed(Constructed::Record(fields))),
+        ))))
+    );
+}
+
+#[test]
+fn parse_binders() {
+    assert_eq!(
+        parse("rec a. rec b. a -> b"),
+        Ok(Ty::Recursive(Box::new(Ty::Recursive(Box::new(fun(
+            Ty::BoundVar(1),
+            Ty::BoundVar(0)
+        )))))),
+    );
+    assert_eq!(
+        parse("rec a. (rec a. a) -> a"),
+        Ok(Ty::Recursive(Box::new(fun(
+            Ty::Recursive(Box::new(Ty::BoundVar(0))),
+            Ty::BoundVar(0)
+        )))),
+    );
+}
+
+#[test]
+fn parse_precedence() {
+    assert_eq!(
+        parse("(bool -> bool) -> bool -> bool"),
+        Ok(fun(fun(bool(), bool()), fun(bool(), bool())))
+    );
+    assert_eq!(
+        parse("⊥ ⊔ bool -> top & 'a"),
+        Ok(Ty::Add(
+            Box::new(Ty::Add(Box::new(Ty::Zero), Box::new(fun(bool(), Ty::Zero)))),
+            Box::new(Ty::UnboundVar('a')),
+        ))
+    );
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(parse("bool ->"), error(7..7, "expected a type"));
+    assert_eq!(parse("bool bool"), error(5..9, "expected end of input"));
+    assert_eq!(parse("{x bool}"), error(3..7, "expected `:`"));
+    assert_eq!(parse("foo"), error(0..3, "expected a type"));
+    assert_eq!(parse("'ab"), error(1..3, "invalid type variable `'ab`"));
+    assert_eq!(parse("rec . a"), error(4..5, "expected an identifier"));
+    assert_eq!(parse("(bool"), error(5..5, "expected `)`"));
+    assert_eq!(parse("(bool, bool"), error(11..11, "expected `)`"));
+    assert_eq!(
+        parse("(bool, bool)"),
+        error(0..12, "tuple types are not supported")
+    );
+    assert_eq!(parse("()"), error(0..2, "tuple types are not supported"));
+}
+
+proptest! {
+    #![proptest_config(Config {
+        cases: 1024,
+        timeout: 10000,
+        ..Config::default()
+    })]
+
+    #[test]
+    fn parse_print_pos(ty in arb_polar_ty(Polarity::Pos)) {
+        let printed = ty.display(Polarity::Pos).to_string();
+        let parsed = parse(&printed).unwrap();
+        prop_assert_eq!(parsed.display(Polarity::Pos).to_string(), printed);
+    }
+
+    #[test]
+    fn parse_print_neg(ty in arb_polar_ty(Polarity::Neg)) {
+        let printed = ty.display(Polarity::Neg).ascii().to_string();
+        let parsed = parse(&printed).unwrap();
+        prop_assert_eq!(parsed.display(Polarity::Neg).ascii().to_string(), printed);
+    }
+}
@@ -0,0 +1,212 @@
+#[cfg(test)]
+mod tests;
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::polar::Ty;
+use crate::Polarity;
+
+/// Printing of the type constructors in a [`Ty`].
+pub trait Print<V>: Sized {
+    /// The precedence of this constructor, used to decide where parentheses are needed.
+    fn precedence(&self) -> Precedence {
+        Precedence::Atom
+    }
+
+    /// Prints this constructor, using `print` to print its type parameters. Each parameter is
+    /// given with its polarity relative to the constructor and the lowest precedence it can be
+    /// printed at without parentheses.
+    fn print<'a, F>(&'a self, f: &mut fmt::Formatter, print: F) -> fmt::Result
+    where
+        V: 'a,
+        F: FnMut(&mut fmt::Formatter, Polarity, Precedence, &'a Ty<Self, V>) -> fmt::Result;
+}
+
+/// The binding strength of a type, from loosest to tightest.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Precedence {
+    /// A recursive type, which extends as far to the right as possible.
+    Recursive,
+    /// A join or meet of types.
+    Add,
+    /// An infix or prefix constructor, such as a function type.
+    Operator,
+    /// A type variable, or a delimited constructor such as a record.
+    Atom,
+}
+
+/// Displays a [`Ty`] with the given polarity, created by [`Ty::display`].
+pub struct Display<'a, B, V> {
+    ty: &'a Ty<B, V>,
+    pol: Polarity,
+    ascii: bool,
+}
+
+struct Printer<'a, V> {
+    ascii: bool,
+    // Names of the unbound variables, in order of first occurrence.
+    vars: HashMap<&'a V, usize>,
+    // Number of recursive types enclosing the current type.
+    depth: usize,
+}
+
+impl<B, V> Ty<B, V> {
+    /// Displays this type, printing joins or meets as `⊔` or `⊓` and empty types as `⊥` or `⊤`
+    /// depending on `pol`.
+    ///
+    /// Unbound variables are named `'a`, `'b`, ... in order of occurrence and recursive types
+    /// bind names `a`, `b`, ... by depth.
+    pub fn display(&self, pol: Polarity) -> Display<'_, B, V> {
+        Display {
+            ty: self,
+            pol,
+            ascii: false,
+        }
+    }
+}
+
+impl<'a, B, V> Display<'a, B, V> {
+    /// Use `|` and `&` for joins and meets and `bot` and `top` for empty types.
+    pub fn ascii(self) -> Self {
+        Display {
+            ascii: true,
+            ..self
+        }
+    }
+}
+
+impl<'a, B, V> fmt::Display for Display<'a, B, V>
+where
+    B: Print<V>,
+    V: Eq + Hash,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut printer = Printer {
+            ascii: self.ascii,
+            vars: HashMap::new(),
+            depth: 0,
+        };
+        printer.print(f, self.pol, Precedence::Recursive, self.ty)
+    }
+}
+
+impl<'a, V> Printer<'a, V>
+where
+    V: Eq + Hash,
+{
+    fn print<B>(
+        &mut self,
+        f: &mut fmt::Formatter,
+        pol: Polarity,
+        prec: Precedence,
+        ty: &'a Ty<B, V>,
+    ) -> fmt::Result
+    where
+        B: Print<V>,
+    {
+        if precedence(ty) < prec {
+            f.write_str("(")?;
+            self.print_ty(f, pol, ty)?;
+            f.write_str(")")
+        } else {
+            self.print_ty(f, pol, ty)
+        }
+    }
+
+    fn print_ty<B>(
+        &mut self,
+        f: &mut fmt::Formatter,
+        pol: Polarity,
+        ty: &'a Ty<B, V>,
+    ) -> fmt::Result
+    where
+        B: Print<V>,
+    {
+        match ty {
+            Ty::Zero => f.write_str(match (pol, self.ascii) {
+                (Polarity::Pos, false) => "⊥",
+                (Polarity::Neg, false) => "⊤",
+                (Polarity::Pos, true) => "bot",
+                (Polarity::Neg, true) => "top",
+            }),
+            Ty::Add(l, r) => {
+                self.print(f, pol, Precedence::Add, l)?;
+                f.write_str(match (pol, self.ascii) {
+                    (Polarity::Pos, false) => " ⊔ ",
+                    (Polarity::Neg, false) => " ⊓ ",
+                    (Polarity::Pos, true) => " | ",
+                    (Polarity::Neg, true) => " & ",
+                })?;
+                self.print(f, pol, Precedence::Add, r)
+            }
+            Ty::UnboundVar(var) => {
+                let next = self.vars.len();
+                let idx = *self.vars.entry(var).or_insert(next);
+                f.write_str("'")?;
+                write_name(f, idx)
+            }
+            Ty::BoundVar(idx) => match self.depth.checked_sub(idx + 1) {
+                Some(binder) => write_name(f, binder),
+                None => write!(f, "#{}", idx),
+            },
+            Ty::Constructed(con) => con.print(f, |f, rel_pol, prec, ty| {
+                self.print(f, pol * rel_pol, prec, ty)
+            }),
+            Ty::Recursive(inner) => {
+                f.write_str("rec ")?;
+                write_name(f, self.depth)?;
+                f.write_str(". ")?;
+
+                self.depth += 1;
+                let result = self.print(f, pol, Precedence::Recursive, inner);
+                self.depth -= 1;
+                result
+            }
+        }
+    }
+}
+
+/// Prints fields such as `x: t, y: u`, for constructors like records which map labels to
+/// covariant type parameters.
+pub fn print_fields<'a, B, V, T, F>(
+    f: &mut fmt::Formatter,
+    fields: &'a BTreeMap<Rc<str>, T>,
+    print: &mut F,
+) -> fmt::Result
+where
+    B: 'a,
+    V: 'a,
+    T: Borrow<Ty<B, V>>,
+    F: FnMut(&mut fmt::Formatter, Polarity, Precedence, &'a Ty<B, V>) -> fmt::Result,
+{
+    for (idx, (label, ty)) in fields.iter().enumerate() {
+        if idx != 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}: ", label)?;
+        print(f, Polarity::Pos, Precedence::Recursive, ty.borrow())?;
+    }
+    Ok(())
+}
+
+fn precedence<B: Print<V>, V>(ty: &Ty<B, V>) -> Precedence {
+    match ty {
+        Ty::Recursive(_) => Precedence::Recursive,
+        Ty::Add(..) => Precedence::Add,
+        Ty::Constructed(con) => con.precedence(),
+        Ty::Zero | Ty::UnboundVar(_) | Ty::BoundVar(_) => Precedence::Atom,
+    }
+}
+
+// Names variables `a` to `z`, then `a1` to `z1` and so on.
+fn write_name(f: &mut fmt::Formatter, idx: usize) -> fmt::Result {
+    let letter = (b'a' + (idx % 26) as u8) as char;
+    match idx / 26 {
+        0 => write!(f, "{}", letter),
+        n => write!(f, "{}{}", letter, n),
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::polar::Ty;
+use crate::tests::Constructed;
+use crate::Polarity;
+
+fn bool() -> Ty<Constructed, char> {
+    Ty::Constructed(Constructed::Bool)
+}
+
+fn fun(d: Ty<Constructed, char>, r: Ty<Constructed, char>) -> Ty<Constructed, char> {
+    Ty::Constructed(Constructed::Fun(Box::new(d), Box::new(r)))
+}
+
+fn add(l: Ty<Constructed, char>, r: Ty<Constructed, char>) -> Ty<Constructed, char> {
+    Ty::Add(Box::new(l), Box::new(r))
+}
+
+fn rec(ty: Ty<Constructed, char>) -> Ty<Constructed, char> {
+    Ty::Recursive(Box::new(ty))
+}
+
+fn print(ty: &Ty<Constructed, char>, pol: Polarity) -> String {
+    ty.display(pol).to_string()
+}
+
+#[test]
+fn print_vars() {
+    let ty = fun(
+        Ty::UnboundVar('x'),
+        add(Ty::UnboundVar('y'), Ty::UnboundVar('x')),
+    );
+    assert_eq!(print(&ty, Polarity::Pos), "'a -> ('b ⊔ 'a)");
+}
+
+#[test]
+fn print_polarity() {
+    let ty = add(bool(), Ty::Zero);
+    assert_eq!(print(&ty, Polarity::Pos), "bool ⊔ ⊥");
+    assert_eq!(print(&ty, Polarity::Neg), "bool ⊓ ⊤");
+
+    let ty = fun(add(bool(), Ty::Zero), add(bool(), Ty::Zero));
+    assert_eq!(print(&ty, Polarity::Pos), "(bool ⊓ ⊤) -> (bool ⊔ ⊥)");
+    assert_eq!(print(&ty, Polarity::Neg), "(bool ⊔ ⊥) -> (bool ⊓ ⊤)");
+}
+
+#[test]
+fn print_ascii() {
+    let ty = fun(add(bool(), Ty::Zero), add(bool(), Ty::Zero));
+    assert_eq!(
+        ty.display(Polarity::Pos).ascii().to_string(),
+        "(bool & top) -> (bool | bot)"
+    );
+}
+
+#[test]
+fn print_recursive() {
+    let ty = rec(fun(bool(), Ty::BoundVar(0)));
+    assert_eq!(print(&ty, Polarity::Pos), "rec a. bool -> a");
+
+    let ty = rec(fun(
+        rec(fun(Ty::BoundVar(1), Ty::BoundVar(0))),
+        Ty::BoundVar(0),
+    ));
+    assert_eq!(print(&ty, Polarity::Pos), "rec a. (rec b. a -> b) -> a");
+}
+
+#[test]
+fn print_precedence() {
+    let ty = fun(fun(bool(), bool()), fun(bool(), bool()));
+    assert_eq!(print(&ty, Polarity::Pos), "(bool -> bool) -> bool -> bool");
+
+    let ty = add(fun(bool(), bool()), rec(add(bool(), Ty::BoundVar(0))));
+    assert_eq!(
+        print(&ty, Polarity::Pos),
+        "bool -> bool ⊔ (rec a. bool ⊔ a)"
+    );
+}
+
+#[test]
+fn print_record() {
+    let mut fields = BTreeMap::new();
+    fields.insert(Rc::from("x"), Box::new(rec(add(bool(), Ty::BoundVar(0)))));
+    fields.insert(Rc::from("y"), Box::new(Ty::Zero));
+    let ty = Ty::Constructed(Constructed::Record(fields));
+    assert_eq!(print(&ty, Polarity::Pos), "{x: rec a. bool ⊔ a, y: ⊥}");
+    assert_eq!(
+        print(
+            &Ty::Constructed(Constructed::Record(BTreeMap::new())),
+            Polarity::Neg
+        ),
+        "{}"
+    );
+}
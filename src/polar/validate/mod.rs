@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::auto::Build;
+use crate::polar::Ty;
+use crate::{Constructor, Label, Polarity};
+
+/// A reason a [`Ty`] cannot be built into an automaton, found by [`Ty::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidateError {
+    /// A bound variable with an index greater than the number of enclosing recursive types.
+    OutOfRange(usize),
+    /// A bound variable which occurs in its recursive type without an enclosing constructor,
+    /// such as `rec a. a` or `rec a. bool ⊔ a`.
+    Unguarded(usize),
+    /// A bound variable which occurs with a different polarity to its recursive type, such as
+    /// `rec a. a -> bool`.
+    Polarity(usize),
+}
+
+impl<B, V> Ty<B, V> {
+    /// Checks that this type is well-formed with polarity `pol`, that is, every bound variable
+    /// refers to an enclosing recursive type with the same polarity, and is guarded by a
+    /// constructor.
+    pub fn validate<C>(&self, pol: Polarity) -> Result<(), ValidateError>
+    where
+        B: Build<C, V>,
+        C: Constructor,
+    {
+        validate(self, pol, &mut Vec::new(), 0)
+    }
+}
+
+// `recs` holds the polarity of each enclosing recursive type, innermost last, and `unguarded` is
+// the number of them not separated from `ty` by a constructor.
+fn validate<B, C, V>(
+    ty: &Ty<B, V>,
+    pol: Polarity,
+    recs: &mut Vec<Polarity>,
+    unguarded: usize,
+) -> Result<(), ValidateError>
+where
+    B: Build<C, V>,
+    C: Constructor,
+{
+    match ty {
+        Ty::Zero | Ty::UnboundVar(_) => Ok(()),
+        Ty::BoundVar(idx) => {
+            if *idx >= recs.len() {
+                Err(ValidateError::OutOfRange(*idx))
+            } else if *idx < unguarded {
+                Err(ValidateError::Unguarded(*idx))
+            } else if recs[recs.len() - idx - 1] != pol {
+                Err(ValidateError::Polarity(*idx))
+            } else {
+                Ok(())
+            }
+        }
+        Ty::Add(l, r) => {
+            validate(l, pol, recs, unguarded)?;
+            validate(r, pol, recs, unguarded)
+        }
+        Ty::Recursive(inner) => {
+            recs.push(pol);
+            let result = validate(inner, pol, recs, unguarded + 1);
+            recs.pop();
+            result
+        }
+        Ty::Constructed(con) => {
+            let mut result = Ok(());
+            con.visit_params(|label, ty| {
+                if result.is_ok() {
+                    result = validate(ty, pol * label.polarity(), recs, 0);
+                }
+            });
+            result
+        }
+    }
+}
+
+impl fmt::Display for ValidateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidateError::OutOfRange(idx) => write!(f, "bound variable {} is out of range", idx),
+            ValidateError::Unguarded(idx) => write!(f, "bound variable {} is unguarded", idx),
+            ValidateError::Polarity(idx) => {
+                write!(f, "bound variable {} has the wrong polarity", idx)
+            }
+        }
+    }
+}
+
+impl Error for ValidateError {}
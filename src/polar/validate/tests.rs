@@ -0,0 +1,79 @@
+use crate::auto::{flow, Automaton};
+use crate::polar::{Ty, ValidateError};
+use crate::tests::{Constructed, Constructor};
+use crate::Polarity;
+
+fn validate(input: &str, pol: Polarity) -> Result<(), ValidateError> {
+    let ty: Ty<Constructed, char> = Ty::parse(input).unwrap();
+    ty.validate(pol)
+}
+
+#[test]
+fn validate_valid() {
+    assert_eq!(validate("rec a. bool -> a", Polarity::Pos), Ok(()));
+    assert_eq!(validate("rec a. {x: a} ⊔ 'b", Polarity::Neg), Ok(()));
+    assert_eq!(
+        validate("rec a. bool -> rec b. a ⊔ (bool -> b)", Polarity::Pos),
+        Ok(())
+    );
+}
+
+#[test]
+fn validate_out_of_range() {
+    let ty: Ty<Constructed, char> = Ty::BoundVar(0);
+    assert_eq!(
+        ty.validate(Polarity::Pos),
+        Err(ValidateError::OutOfRange(0))
+    );
+
+    let ty: Ty<Constructed, char> = Ty::Recursive(Box::new(Ty::Constructed(Constructed::Fun(
+        Box::new(Ty::Zero),
+        Box::new(Ty::BoundVar(1)),
+    ))));
+    assert_eq!(
+        ty.validate(Polarity::Pos),
+        Err(ValidateError::OutOfRange(1))
+    );
+}
+
+#[test]
+fn validate_unguarded() {
+    assert_eq!(
+        validate("rec a. a", Polarity::Pos),
+        Err(ValidateError::Unguarded(0))
+    );
+    assert_eq!(
+        validate("rec a. bool ⊔ a", Polarity::Pos),
+        Err(ValidateError::Unguarded(0))
+    );
+    assert_eq!(
+        validate("rec a. rec b. a", Polarity::Neg),
+        Err(ValidateError::Unguarded(1))
+    );
+}
+
+#[test]
+fn validate_polarity() {
+    assert_eq!(
+        validate("rec a. a -> bool", Polarity::Pos),
+        Err(ValidateError::Polarity(0))
+    );
+    assert_eq!(
+        validate("rec a. a -> bool", Polarity::Neg),
+        Err(ValidateError::Polarity(0))
+    );
+    assert_eq!(
+        validate("rec a. (a -> bool) -> bool", Polarity::Neg),
+        Ok(())
+    );
+}
+
+#[test]
+fn build_invalid() {
+    let ty: Ty<Constructed<flow::Pair>, flow::Pair> = Ty::Recursive(Box::new(Ty::BoundVar(0)));
+    let mut auto = Automaton::<Constructor>::new();
+    assert_eq!(
+        auto.build_polar_simple(Polarity::Pos, &ty),
+        Err(ValidateError::Unguarded(0))
+    );
+}
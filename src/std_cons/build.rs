@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use super::{Constructor, Label};
+use crate::auto::{Build, Decompile, StateSet};
+use crate::polar::{print_fields, Parse, ParseError, Parser, Precedence, Print, Ty};
+use crate::Polarity;
+
+/// A type constructor in a polar type, which can be built into a [`Constructor`].
+///
+/// Types are written as `int`, `t -> u`, `{x: t, y: u}`, `[A: t, B: u]`, `(t, u)` and `ref t`,
+/// where any identifier not bound by a recursive type is a base type. A reference whose read and
+/// write types differ is written `ref<t, u>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Constructed<V, N = Rc<str>> {
+    Base(N),
+    Fun(Box<Ty<Constructed<V, N>, V>>, Box<Ty<Constructed<V, N>, V>>),
+    Record(BTreeMap<Rc<str>, Ty<Constructed<V, N>, V>>),
+    Variant(BTreeMap<Rc<str>, Ty<Constructed<V, N>, V>>),
+    Tuple(Vec<Ty<Constructed<V, N>, V>>),
+    Ref(Box<Ty<Constructed<V, N>, V>>, Box<Ty<Constructed<V, N>, V>>),
+}
+
+impl<V, N: Ord + Clone> Build<Constructor<N>, V> for Constructed<V, N> {
+    fn map<'a, F>(&'a self, mut mapper: F) -> Constructor<N>
+    where
+        V: 'a,
+        F: FnMut(Label, &'a Ty<Self, V>) -> StateSet,
+    {
+        match self {
+            Constructed::Base(name) => Constructor::Base(name.clone()),
+            Constructed::Fun(d, r) => {
+                Constructor::Fun(mapper(Label::Domain, d), mapper(Label::Range, r))
+            }
+            Constructed::Record(fields) => Constructor::Record(
+                fields
+                    .iter()
+                    .map(|(label, ty)| (label.clone(), mapper(Label::Field(label.clone()), ty)))
+                    .collect(),
+            ),
+            Constructed::Variant(tags) => Constructor::Variant(
+                tags.iter()
+                    .map(|(label, ty)| (label.clone(), mapper(Label::Tag(label.clone()), ty)))
+                    .collect(),
+            ),
+            Constructed::Tuple(elems) => Constructor::Tuple(
+                elems
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, ty)| mapper(Label::Index(idx), ty))
+                    .collect(),
+            ),
+            Constructed::Ref(r, w) => {
+                Constructor::Ref(mapper(Label::Read, r), mapper(Label::Write, w))
+            }
+        }
+    }
+
+    fn visit_params<'a, F>(&'a self, mut visit: F)
+    where
+        V: 'a,
+        F: FnMut(Label, &'a Ty<Self, V>),
+    {
+        match self {
+            Constructed::Base(_) => (),
+            Constructed::Fun(d, r) => {
+                visit(Label::Domain, d);
+                visit(Label::Range, r);
+            }
+            Constructed::Record(fields) => {
+                for (label, ty) in fields {
+                    visit(Label::Field(label.clone()), ty);
+                }
+            }
+            Constructed::Variant(tags) => {
+                for (label, ty) in tags {
+                    visit(Label::Tag(label.clone()), ty);
+                }
+            }
+            Constructed::Tuple(elems) => {
+                for (idx, ty) in elems.iter().enumerate() {
+                    visit(Label::Index(idx), ty);
+                }
+            }
+            Constructed::Ref(r, w) => {
+                visit(Label::Read, r);
+                visit(Label::Write, w);
+            }
+        }
+    }
+}
+
+impl<V, N: Ord + Clone> Decompile<Constructor<N>, V> for Constructed<V, N> {
+    fn decompile<F>(con: &Constructor<N>, mut mapper: F) -> Self
+    where
+        F: FnMut(Label, &StateSet) -> Ty<Self, V>,
+    {
+        match con {
+            Constructor::Base(name) => Constructed::Base(name.clone()),
+            Constructor::Fun(d, r) => Constructed::Fun(
+                Box::new(mapper(Label::Domain, d)),
+                Box::new(mapper(Label::Range, r)),
+            ),
+            Constructor::Record(fields) => Constructed::Record(
+                fields
+                    .iter()
+                    .map(|(label, set)| (label.clone(), mapper(Label::Field(label.clone()), set)))
+                    .collect(),
+            ),
+            Constructor::Variant(tags) => Constructed::Variant(
+                tags.iter()
+                    .map(|(label, set)| (label.clone(), mapper(Label::Tag(label.clone()), set)))
+                    .collect(),
+            ),
+            Constructor::Tuple(elems) => Constructed::Tuple(
+                elems
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, set)| mapper(Label::Index(idx), set))
+                    .collect(),
+            ),
+            Constructor::Ref(r, w) => Constructed::Ref(
+                Box::new(mapper(Label::Read, r)),
+                Box::new(mapper(Label::Write, w)),
+            ),
+        }
+    }
+}
+
+impl<V, N> Print<V> for Constructed<V, N>
+where
+    V: PartialEq,
+    N: fmt::Display + PartialEq,
+{
+    fn precedence(&self) -> Precedence {
+        match self {
+            Constructed::Fun(..) => Precedence::Operator,
+            _ => Precedence::Atom,
+        }
+    }
+
+    fn print<'a, F>(&'a self, f: &mut fmt::Formatter, mut print: F) -> fmt::Result
+    where
+        V: 'a,
+        F: FnMut(&mut fmt::Formatter, Polarity, Precedence, &'a Ty<Self, V>) -> fmt::Result,
+    {
+        match self {
+            Constructed::Base(name) => write!(f, "{}", name),
+            Constructed::Fun(d, r) => {
+                print(f, Polarity::Neg, Precedence::Atom, d)?;
+                f.write_str(" -> ")?;
+                print(f, Polarity::Pos, Precedence::Operator, r)
+            }
+            Constructed::Record(fields) => {
+                f.write_str("{")?;
+                print_fields(f, fields, &mut print)?;
+                f.write_str("}")
+            }
+            Constructed::Variant(tags) => {
+                f.write_str("[")?;
+                print_fields(f, tags, &mut print)?;
+                f.write_str("]")
+            }
+            Constructed::Tuple(elems) => {
+                f.write_str("(")?;
+                for (idx, ty) in elems.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_str(", ")?;
+                    }
+                    print(f, Polarity::Pos, Precedence::Recursive, ty)?;
+                }
+                if elems.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+            Constructed::Ref(r, w) if r == w => {
+                f.write_str("ref ")?;
+                print(f, Polarity::Pos, Precedence::Atom, r)
+            }
+            Constructed::Ref(r, w) => {
+                f.write_str("ref<")?;
+                print(f, Polarity::Pos, Precedence::Recursive, r)?;
+                f.write_str(", ")?;
+                print(f, Polarity::Neg, Precedence::Recursive, w)?;
+                f.write_str(">")
+            }
+        }
+    }
+}
+
+impl<V, N> Parse<V> for Constructed<V, N>
+where
+    V: FromStr + Clone,
+    N: for<'a> From<&'a str> + Clone,
+{
+    fn parse_atom(parser: &mut Parser<'_, Self, V>) -> Result<Option<Self>, ParseError> {
+        if parser.eat("{") {
+            Ok(Some(Constructed::Record(parser.parse_fields("}")?)))
+        } else if parser.eat("[") {
+            Ok(Some(Constructed::Variant(parser.parse_fields("]")?)))
+        } else if parser.eat("ref") {
+            if parser.eat("<") {
+                let r = parser.parse(Precedence::Recursive)?;
+                parser.expect(",")?;
+                let w = parser.parse(Precedence::Recursive)?;
+                parser.expect(">")?;
+                Ok(Some(Constructed::Ref(Box::new(r), Box::new(w))))
+            } else {
+                // The same type is used for both reading and writing.
+                let ty = parser.parse(Precedence::Atom)?;
+                Ok(Some(Constructed::Ref(Box::new(ty.clone()), Box::new(ty))))
+            }
+        } else if let Some(name) = parser.ident() {
+            Ok(Some(Constructed::Base(N::from(name))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_infix(
+        lhs: Ty<Self, V>,
+        parser: &mut Parser<'_, Self, V>,
+    ) -> Result<Result<Self, Ty<Self, V>>, ParseError> {
+        if parser.eat("->") {
+            let rhs = parser.parse(Precedence::Operator)?;
+            Ok(Ok(Constructed::Fun(Box::new(lhs), Box::new(rhs))))
+        } else {
+            Ok(Err(lhs))
+        }
+    }
+
+    fn parse_tuple(elems: Vec<Ty<Self, V>>) -> Option<Self> {
+        Some(Constructed::Tuple(elems))
+    }
+}
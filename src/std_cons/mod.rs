@@ -0,0 +1,386 @@
+//! A general purpose set of type constructors: functions, records, polymorphic variants, tuples
+//! and nominal base types.
+
+mod build;
+#[cfg(test)]
+mod tests;
+
+pub use self::build::Constructed;
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+use im::OrdMap;
+use itertools::{merge_join_by, EitherOrBoth};
+
+use crate::auto::interface::{self, Encode, Reader, Writer};
+use crate::auto::StateSet;
+use crate::Polarity;
+
+/// A type constructor in an automaton.
+///
+/// Base types are compared nominally, so the set of base types can be extended by choosing `N`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Constructor<N = Rc<str>> {
+    /// A base type such as `int` or `bool`.
+    Base(N),
+    /// A function type, with its domain and range.
+    Fun(StateSet, StateSet),
+    /// A record type. A record with more fields is a subtype of one with fewer.
+    Record(OrdMap<Rc<str>, StateSet>),
+    /// A polymorphic variant type. A variant with fewer tags is a subtype of one with more.
+    Variant(OrdMap<Rc<str>, StateSet>),
+    /// A tuple type. Tuples of different lengths are unrelated.
+    Tuple(Vec<StateSet>),
+    /// A mutable reference type. Its type parameter is invariant, so it is split into the types
+    /// of values read from and written to the reference.
+    Ref(StateSet, StateSet),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Component<N = Rc<str>> {
+    Base(N),
+    Fun,
+    Record,
+    Variant,
+    Tuple(usize),
+    Ref,
+}
+
+/// The label of a type parameter of a [`Constructor`].
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum Label {
+    Domain,
+    Range,
+    Field(Rc<str>),
+    Tag(Rc<str>),
+    Index(usize),
+    Read,
+    Write,
+}
+
+impl<N: Ord + Clone> crate::Constructor for Constructor<N> {
+    type Label = Label;
+    type Component = Component<N>;
+
+    fn component(&self) -> Self::Component {
+        match self {
+            Constructor::Base(name) => Component::Base(name.clone()),
+            Constructor::Fun(..) => Component::Fun,
+            Constructor::Record(_) => Component::Record,
+            Constructor::Variant(_) => Component::Variant,
+            Constructor::Tuple(elems) => Component::Tuple(elems.len()),
+            Constructor::Ref(..) => Component::Ref,
+        }
+    }
+
+    fn join(&mut self, other: &Self, pol: Polarity) {
+        match (self, other) {
+            (Constructor::Base(_), Constructor::Base(_)) => (),
+            (Constructor::Fun(ld, lr), Constructor::Fun(rd, rr))
+            | (Constructor::Ref(ld, lr), Constructor::Ref(rd, rr)) => {
+                ld.union(rd);
+                lr.union(rr);
+            }
+            // A join of records has only their common fields, and a join of variants has all
+            // of their tags. Meets are the other way around.
+            (Constructor::Record(lhs), Constructor::Record(rhs)) => join_fields(lhs, rhs, pol),
+            (Constructor::Variant(lhs), Constructor::Variant(rhs)) => join_fields(lhs, rhs, -pol),
+            (Constructor::Tuple(lhs), Constructor::Tuple(rhs)) => {
+                debug_assert_eq!(lhs.len(), rhs.len());
+                for (l, r) in lhs.iter_mut().zip(rhs) {
+                    l.union(r);
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn visit_params_intersection<F, E>(&self, other: &Self, mut visit: F) -> Result<(), E>
+    where
+        F: FnMut(Self::Label, &StateSet, &StateSet) -> Result<(), E>,
+    {
+        match (self, other) {
+            (Constructor::Base(_), Constructor::Base(_)) => Ok(()),
+            (Constructor::Fun(ld, lr), Constructor::Fun(rd, rr)) => {
+                visit(Label::Domain, ld, rd)?;
+                visit(Label::Range, lr, rr)
+            }
+            (Constructor::Record(lhs), Constructor::Record(rhs)) => {
+                visit_fields(lhs, rhs, |label, l, r| visit(Label::Field(label), l, r))
+            }
+            (Constructor::Variant(lhs), Constructor::Variant(rhs)) => {
+                visit_fields(lhs, rhs, |label, l, r| visit(Label::Tag(label), l, r))
+            }
+            (Constructor::Tuple(lhs), Constructor::Tuple(rhs)) => lhs
+                .iter()
+                .zip(rhs)
+                .enumerate()
+                .try_for_each(|(idx, (l, r))| visit(Label::Index(idx), l, r)),
+            (Constructor::Ref(lr, lw), Constructor::Ref(rr, rw)) => {
+                visit(Label::Read, lr, rr)?;
+                visit(Label::Write, lw, rw)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn map<F>(self, mut mapper: F) -> Self
+    where
+        F: FnMut(Self::Label, StateSet) -> StateSet,
+    {
+        match self {
+            Constructor::Base(name) => Constructor::Base(name),
+            Constructor::Fun(d, r) => {
+                Constructor::Fun(mapper(Label::Domain, d), mapper(Label::Range, r))
+            }
+            Constructor::Record(fields) => Constructor::Record(
+                fields
+                    .into_iter()
+                    .map(|(label, set)| (label.clone(), mapper(Label::Field(label), set)))
+                    .collect(),
+            ),
+            Constructor::Variant(tags) => Constructor::Variant(
+                tags.into_iter()
+                    .map(|(label, set)| (label.clone(), mapper(Label::Tag(label), set)))
+                    .collect(),
+            ),
+            Constructor::Tuple(elems) => Constructor::Tuple(
+                elems
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, set)| mapper(Label::Index(idx), set))
+                    .collect(),
+            ),
+            Constructor::Ref(r, w) => {
+                Constructor::Ref(mapper(Label::Read, r), mapper(Label::Write, w))
+            }
+        }
+    }
+}
+
+impl<N: Ord> PartialOrd for Constructor<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Constructor::Base(lhs), Constructor::Base(rhs)) if lhs == rhs => Some(Ordering::Equal),
+            (Constructor::Fun(..), Constructor::Fun(..))
+            | (Constructor::Ref(..), Constructor::Ref(..)) => Some(Ordering::Equal),
+            (Constructor::Record(lhs), Constructor::Record(rhs)) => {
+                cmp_keys(lhs, rhs).map(Ordering::reverse)
+            }
+            (Constructor::Variant(lhs), Constructor::Variant(rhs)) => cmp_keys(lhs, rhs),
+            (Constructor::Tuple(lhs), Constructor::Tuple(rhs)) if lhs.len() == rhs.len() => {
+                Some(Ordering::Equal)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl crate::Label for Label {
+    fn polarity(&self) -> Polarity {
+        match self {
+            Label::Domain | Label::Write => Polarity::Neg,
+            Label::Range | Label::Field(_) | Label::Tag(_) | Label::Index(_) | Label::Read => {
+                Polarity::Pos
+            }
+        }
+    }
+}
+
+/// Displays the outermost structure of a constructor, for error messages such as those created
+/// by [`BiunifyError::display`](crate::BiunifyError::display). Type parameters are elided, so a
+/// record is displayed like `{x: ..., y: ...}`.
+impl<N: fmt::Display> fmt::Display for Constructor<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Constructor::Base(name) => write!(f, "{}", name),
+            Constructor::Fun(..) => f.write_str("... -> ..."),
+            Constructor::Record(fields) => {
+                f.write_str("{")?;
+                fmt_keys(f, fields)?;
+                f.write_str("}")
+            }
+            Constructor::Variant(tags) => {
+                f.write_str("[")?;
+                fmt_keys(f, tags)?;
+                f.write_str("]")
+            }
+            Constructor::Tuple(elems) => {
+                f.write_str("(")?;
+                for idx in 0..elems.len() {
+                    if idx != 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_str("...")?;
+                }
+                if elems.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+            Constructor::Ref(..) => f.write_str("ref ..."),
+        }
+    }
+}
+
+fn fmt_keys(f: &mut fmt::Formatter, map: &OrdMap<Rc<str>, StateSet>) -> fmt::Result {
+    for (idx, key) in map.keys().enumerate() {
+        if idx != 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}: ...", key)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Label::Domain => f.write_str("the argument"),
+            Label::Range => f.write_str("the result"),
+            Label::Field(label) => write!(f, "field `{}`", label),
+            Label::Tag(tag) => write!(f, "tag `{}`", tag),
+            Label::Index(idx) => write!(f, "element {}", idx),
+            Label::Read => f.write_str("the read type"),
+            Label::Write => f.write_str("the write type"),
+        }
+    }
+}
+
+/// Encodes base types by their name, so they can be shared between interface files.
+impl<N> Encode for Constructor<N>
+where
+    N: Ord + Clone + AsRef<str> + for<'a> From<&'a str>,
+{
+    fn encode(&self, writer: &mut Writer) {
+        match self {
+            Constructor::Base(name) => {
+                writer.write_u8(0);
+                writer.write_str(name.as_ref());
+            }
+            Constructor::Fun(d, r) => {
+                writer.write_u8(1);
+                writer.write_state_set(d);
+                writer.write_state_set(r);
+            }
+            Constructor::Record(fields) => {
+                writer.write_u8(2);
+                encode_fields(writer, fields);
+            }
+            Constructor::Variant(tags) => {
+                writer.write_u8(3);
+                encode_fields(writer, tags);
+            }
+            Constructor::Tuple(elems) => {
+                writer.write_u8(4);
+                writer.write_u32(elems.len() as u32);
+                for elem in elems {
+                    writer.write_state_set(elem);
+                }
+            }
+            Constructor::Ref(r, w) => {
+                writer.write_u8(5);
+                writer.write_state_set(r);
+                writer.write_state_set(w);
+            }
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, interface::Error> {
+        match reader.read_u8()? {
+            0 => Ok(Constructor::Base(N::from(reader.read_str()?))),
+            1 => Ok(Constructor::Fun(
+                reader.read_state_set()?,
+                reader.read_state_set()?,
+            )),
+            2 => Ok(Constructor::Record(decode_fields(reader)?)),
+            3 => Ok(Constructor::Variant(decode_fields(reader)?)),
+            4 => {
+                let len = reader.read_u32()?;
+                let elems = (0..len)
+                    .map(|_| reader.read_state_set())
+                    .collect::<Result<_, _>>()?;
+                Ok(Constructor::Tuple(elems))
+            }
+            5 => Ok(Constructor::Ref(
+                reader.read_state_set()?,
+                reader.read_state_set()?,
+            )),
+            tag => Err(interface::Error::Invalid(format!(
+                "unknown constructor tag {}",
+                tag
+            ))),
+        }
+    }
+}
+
+fn encode_fields(writer: &mut Writer, map: &OrdMap<Rc<str>, StateSet>) {
+    writer.write_u32(map.len() as u32);
+    for (key, set) in map {
+        writer.write_str(key);
+        writer.write_state_set(set);
+    }
+}
+
+fn decode_fields(reader: &mut Reader<'_>) -> Result<OrdMap<Rc<str>, StateSet>, interface::Error> {
+    let mut map = OrdMap::new();
+    for _ in 0..reader.read_u32()? {
+        let key = Rc::from(reader.read_str()?);
+        if map.insert(key, reader.read_state_set()?).is_some() {
+            return Err(interface::Error::Invalid("duplicate field".to_owned()));
+        }
+    }
+    Ok(map)
+}
+
+// Joins two maps of fields, keeping their intersection for positive polarity and their union for
+// negative polarity.
+fn join_fields(
+    lhs: &mut OrdMap<Rc<str>, StateSet>,
+    rhs: &OrdMap<Rc<str>, StateSet>,
+    pol: Polarity,
+) {
+    let union = |mut l: StateSet, r: StateSet| {
+        l.union(&r);
+        l
+    };
+    *lhs = match pol {
+        Polarity::Pos => lhs.clone().intersection_with(rhs.clone(), union),
+        Polarity::Neg => lhs.clone().union_with(rhs.clone(), union),
+    };
+}
+
+fn visit_fields<F, E>(
+    lhs: &OrdMap<Rc<str>, StateSet>,
+    rhs: &OrdMap<Rc<str>, StateSet>,
+    mut visit: F,
+) -> Result<(), E>
+where
+    F: FnMut(Rc<str>, &StateSet, &StateSet) -> Result<(), E>,
+{
+    merge_join_by(lhs, rhs, |l, r| Ord::cmp(l.0, r.0)).try_for_each(|eob| match eob {
+        EitherOrBoth::Both(l, r) => visit(l.0.clone(), l.1, r.1),
+        _ => Ok(()),
+    })
+}
+
+// Compares the key sets of two maps by inclusion.
+fn cmp_keys<V>(lhs: &OrdMap<Rc<str>, V>, rhs: &OrdMap<Rc<str>, V>) -> Option<Ordering> {
+    let (mut left, mut right) = (false, false);
+    for eob in merge_join_by(lhs.keys(), rhs.keys(), Ord::cmp) {
+        match eob {
+            EitherOrBoth::Left(_) => left = true,
+            EitherOrBoth::Right(_) => right = true,
+            EitherOrBoth::Both(..) => (),
+        }
+    }
+    match (left, right) {
+        (false, false) => Some(Ordering::Equal),
+        (false, true) => Some(Ordering::Less),
+        (true, false) => Some(Ordering::Greater),
+        (true, true) => None,
+    }
+}
@@ -0,0 +1,182 @@
+use std::iter::once;
+
+use proptest::proptest;
+
+use crate::arbitrary::{arb_constraint, arb_std_cons};
+use crate::auto::Automaton;
+use crate::polar::Ty;
+use crate::std_cons::{Constructed, Constructor};
+use crate::Polarity;
+
+fn parse(input: &str) -> Ty<Constructed<char>, char> {
+    Ty::parse(input).unwrap()
+}
+
+fn biunify(lhs: &str, rhs: &str) -> bool {
+    let mut auto: Automaton<Constructor> = Automaton::new();
+    let mut builder = auto.builder::<char>();
+    let lhs = builder.build_polar(Polarity::Pos, &parse(lhs));
+    let rhs = builder.build_polar(Polarity::Neg, &parse(rhs));
+    drop(builder);
+    auto.biunify(lhs, rhs).is_ok()
+}
+
+fn roundtrip(input: &str, pol: Polarity) -> String {
+    let ty = parse(input);
+    assert_eq!(ty.validate(pol), Ok(()));
+
+    let mut nfa = Automaton::new();
+    let id = nfa.builder::<char>().build_polar(pol, &ty);
+
+    let mut dfa: Automaton<Constructor> = Automaton::new();
+    let id = dfa.reduce(&nfa, once((id, pol))).next().unwrap();
+    let ty: Ty<Constructed<usize>, usize> = dfa.to_polar_ty(id, pol);
+    ty.display(pol).to_string()
+}
+
+#[test]
+fn biunify_base() {
+    assert!(biunify("int", "int"));
+    assert!(!biunify("int", "bool"));
+    assert!(biunify("bool", "bool & 'a"));
+    assert!(!biunify("int | bool", "int"));
+}
+
+#[test]
+fn biunify_fun() {
+    assert!(biunify("int -> bool", "int -> bool"));
+    assert!(!biunify("int -> bool", "bool -> bool"));
+    assert!(!biunify("int -> bool", "int -> int"));
+    assert!(!biunify("int -> bool", "{x: int}"));
+}
+
+#[test]
+fn biunify_record() {
+    assert!(biunify("{x: int, y: bool}", "{x: int}"));
+    assert!(!biunify("{x: int}", "{x: int, y: bool}"));
+    assert!(!biunify("{x: int}", "{x: bool}"));
+    assert!(biunify("{x: int, y: bool} | {x: int}", "{x: int}"));
+    assert!(!biunify("{x: int, y: bool} | {x: int}", "{y: bool}"));
+    assert!(biunify("{x: int}", "{x: int} & {x: top}"));
+}
+
+#[test]
+fn biunify_variant() {
+    assert!(biunify("[A: int]", "[A: int, B: bool]"));
+    assert!(!biunify("[A: int, B: bool]", "[A: int]"));
+    assert!(!biunify("[A: int]", "[A: bool]"));
+    assert!(biunify("[A: int] | [B: bool]", "[A: int, B: bool]"));
+    assert!(!biunify("[A: int] | [B: bool]", "[A: int]"));
+    assert!(!biunify("[A: int]", "[A: int, B: bool] & [B: bool]"));
+}
+
+#[test]
+fn biunify_tuple() {
+    assert!(biunify("()", "()"));
+    assert!(biunify("(int, bool)", "(int, bool)"));
+    assert!(!biunify("(int, bool)", "(int, int)"));
+    assert!(!biunify("(int, bool)", "(int, bool, bool)"));
+    assert!(!biunify("(int,)", "int"));
+}
+
+#[test]
+fn biunify_var() {
+    assert!(biunify(
+        "{x: int} -> (int, bool)",
+        "{x: 'a, y: bool} -> ('a, 'b)"
+    ));
+    assert!(!biunify(
+        "{x: int} -> (bool, bool)",
+        "{x: 'a, y: bool} -> ('a, 'b)"
+    ));
+}
+
+#[test]
+fn biunify_ref() {
+    assert!(biunify("ref int", "ref int"));
+    assert!(!biunify("ref int", "ref bool"));
+    assert!(!biunify("ref {x: int, y: int}", "ref {x: int}"));
+    assert!(!biunify("ref {x: int}", "ref {x: int, y: int}"));
+    assert!(biunify(
+        "ref {x: int, y: int}",
+        "ref<{x: int}, {x: int, y: int}>"
+    ));
+    assert!(biunify("ref 'a -> 'a", "ref int -> int"));
+    assert!(!biunify("ref 'a -> 'a", "ref int -> bool"));
+    assert!(biunify("'a -> ref 'a", "int -> ref int"));
+    assert!(!biunify("'a -> ref 'a", "int -> ref bool"));
+    assert!(!biunify("'a -> ref 'a", "{x: int} -> ref {x: int, y: int}"));
+}
+
+#[test]
+fn print_parse() {
+    for input in &[
+        "int",
+        "int -> bool -> bool",
+        "(int -> bool) -> bool",
+        "{x: int, y: [A: (), B: (bool,)]}",
+        "rec a. [Cons: (int, a), Nil: ()]",
+        "ref ref int -> ref (int, bool)",
+        "ref<{x: int}, {x: int, y: int}>",
+    ] {
+        assert_eq!(parse(input).display(Polarity::Pos).to_string(), *input);
+    }
+}
+
+#[test]
+fn decompile() {
+    assert_eq!(
+        roundtrip("[A: int] | [B: bool] | [A: bool]", Polarity::Pos),
+        "[A: bool ⊔ int, B: bool]"
+    );
+    assert_eq!(
+        roundtrip("[A: int, B: bool] & [A: bool]", Polarity::Neg),
+        "[A: bool ⊓ int]"
+    );
+    assert_eq!(
+        roundtrip("{x: int, y: bool} | {x: bool}", Polarity::Pos),
+        "{x: bool ⊔ int}"
+    );
+    assert_eq!(
+        roundtrip("(int, bool) | (bool, bool) | (int,)", Polarity::Pos),
+        "(int,) ⊔ (bool ⊔ int, bool)"
+    );
+    assert_eq!(roundtrip("ref 'a -> 'a", Polarity::Pos), "ref 'a -> 'a");
+    assert_eq!(
+        roundtrip("ref<{x: int}, {x: int, y: int}>", Polarity::Pos),
+        "ref<{x: int}, {x: int, y: int}>"
+    );
+    assert_eq!(
+        roundtrip("ref {x: int} | ref {x: bool}", Polarity::Pos),
+        "ref {x: bool ⊔ int}"
+    );
+}
+
+#[test]
+fn interface() {
+    let input = "rec a. {x: int, y: ref (int, 'a) -> [A: 'a | bool, B: a]}";
+    let expected = roundtrip(input, Polarity::Pos);
+
+    let mut nfa = Automaton::new();
+    let id = nfa
+        .builder::<char>()
+        .build_polar(Polarity::Pos, &parse(input));
+    let bytes = nfa.write_interface(once((id, Polarity::Pos)));
+
+    let mut auto: Automaton<Constructor> = Automaton::new();
+    let id = auto.read_interface(&bytes).unwrap().next().unwrap();
+    let ty: Ty<Constructed<usize>, usize> = auto.to_polar_ty(id, Polarity::Pos);
+    assert_eq!(ty.display(Polarity::Pos).to_string(), expected);
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_constraint((pos, neg) in arb_constraint(arb_std_cons)) {
+        let mut auto: Automaton<Constructor> = Automaton::new();
+        let mut builder = auto.builder::<char>();
+        let pos = builder.build_polar(Polarity::Pos, &pos);
+        let neg = builder.build_polar(Polarity::Neg, &neg);
+        drop(builder);
+        let _ = auto.biunify(pos, neg);
+    }
+}
@@ -1,51 +1,168 @@
-use std::collections::HashSet;
+#[cfg(test)]
+mod tests;
+
+use std::collections::{HashMap, HashSet};
 use std::hash::BuildHasherDefault;
+use std::iter::once;
 
 use seahash::SeaHasher;
+use small_ord_set::SmallOrdSet;
 
 use crate::auto::{flow, Automaton, StateId};
-use crate::Constructor;
+use crate::{Constructor, Label, Polarity};
+
+type Seen = HashSet<(StateId, StateId), BuildHasherDefault<SeaHasher>>;
+// Maps each state of the subsuming scheme to the states of the subsumed scheme it simulates.
+type Relation =
+    HashMap<StateId, (Polarity, SmallOrdSet<[StateId; 2]>), BuildHasherDefault<SeaHasher>>;
 
 impl<C: Constructor> Automaton<C> {
-    pub fn subsume(&self, a: StateId, b: StateId) -> Result<(), ()> {
+    /// Checks whether the type scheme rooted at `a` subsumes the one rooted at `b`, that is,
+    /// whether some instance of `a` is a subtype of `b` (or a supertype, for negative polarity).
+    ///
+    /// Type variables of `b` are treated as rigid. Both `a` and `b` must be reduced.
+    pub fn subsume(&self, a: StateId, b: StateId, pol: Polarity) -> bool {
+        self.subsume_all(once((a, b, pol)))
+    }
+
+    /// Checks whether each type scheme `a` subsumes the corresponding `b`, with a single
+    /// instantiation of the type variables of all `a`s.
+    pub fn subsume_all<I>(&self, roots: I) -> bool
+    where
+        I: IntoIterator<Item = (StateId, StateId, Polarity)>,
+    {
+        let mut rel = HashMap::with_capacity_and_hasher(20, Default::default());
+        for (a, b, pol) in roots {
+            if self.subsume_impl(&mut rel, a, b, pol).is_err() {
+                return false;
+            }
+        }
+
+        // Each flow edge of `a` is a type variable α, which must be instantiated between its
+        // lower bounds (the states of `b` related to negative occurrences of α) and its upper
+        // bounds (the states of `b` related to positive occurrences of α).
         let mut seen = HashSet::with_capacity_and_hasher(20, Default::default());
-        self.subsume_impl(&mut seen, a, b)
+        rel.iter()
+            .filter(|(_, &(pol, _))| pol == Polarity::Neg)
+            .all(|(&an, (_, bns))| {
+                self[an].flow.iter().all(|ap| match rel.get(&ap) {
+                    Some((_, bps)) => bns.iter().all(|&neg| {
+                        bps.iter()
+                            .all(|&pos| self.entails(&mut seen, flow::Pair { neg, pos }))
+                    }),
+                    None => true,
+                })
+            })
     }
 
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
     fn subsume_impl(
         &self,
-        seen: &mut HashSet<(StateId, StateId), BuildHasherDefault<SeaHasher>>,
+        rel: &mut Relation,
         a: StateId,
         b: StateId,
+        pol: Polarity,
     ) -> Result<(), ()> {
-        #[cfg(debug_assertions)]
-        debug_assert_eq!(self[a].pol, self[b].pol);
-
-        if seen.insert((a, b)) {
-            for lcon in self[a].cons.iter() {
-                let rcon = match self[b].cons.get(lcon.component()) {
-                    Some(rcon) if lcon <= rcon => rcon,
-                    _ => return Err(()),
+        debug_assert_eq!(self[a].pol, pol);
+        debug_assert_eq!(self[b].pol, pol);
+
+        let (_, bs) = rel
+            .entry(a)
+            .or_insert_with(|| (pol, SmallOrdSet::default()));
+        if bs.insert(b) {
+            for acon in self[a].cons.iter() {
+                let bcon = match self[b].cons.get(acon.component()) {
+                    Some(bcon) => bcon,
+                    None => return Err(()),
                 };
+                let (sub, sup) = pol.flip(acon, bcon);
+                if !(sub <= sup) {
+                    return Err(());
+                }
 
-                lcon.visit_params_intersection(rcon, |_, l, r| {
-                    self.subsume_impl(seen, l.unwrap_reduced(), r.unwrap_reduced())
+                acon.visit_params_intersection(bcon, |label, l, r| {
+                    self.subsume_impl(
+                        rel,
+                        l.unwrap_reduced(),
+                        r.unwrap_reduced(),
+                        pol * label.polarity(),
+                    )
                 })?;
             }
         }
         Ok(())
     }
 
+    /// Checks whether the flow edge `pair` is admissible, that is, whether adding it does not
+    /// change the meaning of the automaton. If so, the edge is added and `true` is returned,
+    /// otherwise the automaton is left unchanged.
+    ///
+    /// An existing flow edge can be dropped if it is admissible after removing it.
     pub fn admissible(&mut self, pair: flow::Pair) -> bool {
         if self.has_flow(pair) {
             true
         } else {
+            // The new edge acts as the coinductive hypothesis when checking the constructors.
             self.add_flow(pair);
 
-            unimplemented!();
+            let mut seen = HashSet::with_capacity_and_hasher(20, Default::default());
+            seen.insert((pair.neg, pair.pos));
+            if self.entails_cons(&mut seen, pair) {
+                return true;
+            }
 
             self.remove_flow(pair);
             false
         }
     }
+
+    /// Checks whether t⁻ ≤ t⁺ holds for the states in `pair`, treating flow edges as rigid
+    /// type variables.
+    pub(crate) fn entails(&self, seen: &mut Seen, pair: flow::Pair) -> bool {
+        if self.has_flow(pair) || !seen.insert((pair.neg, pair.pos)) {
+            true
+        } else {
+            self.entails_cons(seen, pair)
+        }
+    }
+
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    fn entails_cons(&self, seen: &mut Seen, pair: flow::Pair) -> bool {
+        let cns = &self[pair.neg].cons;
+        let cps = &self[pair.pos].cons;
+        cns.intersection(cps).any(|(cn, cp)| {
+            if !(cn <= cp) {
+                return false;
+            }
+
+            // Assumptions made while checking this pair must be discarded if it fails, as must
+            // those made while checking each candidate pair of parameters.
+            let mut trial = seen.clone();
+            let result = cn.visit_params_intersection(cp, |label, l, r| {
+                let (ns, ps) = label.polarity().flip(l, r);
+                let holds = ns.iter().any(|neg| {
+                    ps.iter().any(|pos| {
+                        let mut attempt = trial.clone();
+                        let holds = self.entails(&mut attempt, flow::Pair { neg, pos });
+                        if holds {
+                            trial = attempt;
+                        }
+                        holds
+                    })
+                });
+                if holds {
+                    Ok(())
+                } else {
+                    Err(())
+                }
+            });
+
+            if result.is_ok() {
+                *seen = trial;
+                true
+            } else {
+                false
+            }
+        })
+    }
 }
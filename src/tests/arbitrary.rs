@@ -1,4 +1,3 @@
-use std::collections::VecDeque;
 use std::rc::Rc;
 
 use lazy_static::lazy_static;
@@ -6,12 +5,11 @@ use proptest::collection::btree_map;
 use proptest::prelude::*;
 use proptest::prop_oneof;
 use proptest::proptest;
-use proptest::strategy::{LazyJust, NewTree, ValueTree};
+use proptest::strategy::LazyJust;
 use proptest::string::string_regex;
-use proptest::test_runner::TestRunner;
-use rand_distr::Exp1;
 
 use super::{Constructed, Constructor};
+use crate::arbitrary;
 use crate::auto::{Automaton, StateId};
 use crate::polar::Ty;
 use crate::Polarity;
@@ -29,94 +27,52 @@ pub fn arb_auto_ty(pol: Polarity) -> BoxedStrategy<(Automaton<Constructor>, Stat
 }
 
 pub fn arb_polar_ty(pol: Polarity) -> BoxedStrategy<Ty<Constructed, char>> {
-    prop_oneof![
-        LazyJust::new(|| Ty::Zero),
-        prop::char::range('a', 'e').prop_map(Ty::UnboundVar),
-        BoundVar.prop_map(Ty::BoundVar),
-    ]
-    .prop_recursive(32, 1000, 8, |inner| {
-        prop_oneof![
-            3 => arb_polar_cons(inner.clone()).prop_map(Ty::Constructed),
-            1 => (inner.clone(), inner.clone()).prop_map(|(l, r)| Ty::Add(Box::new(l), Box::new(r))),
-            1 => inner.prop_map(Box::new).prop_map(Ty::Recursive),
-        ]
-    })
-    .prop_filter("invalid polar type", move |ty| check(pol, ty, &mut VecDeque::new(), 0))
-    .boxed()
+    arb_polar_ty_impl(pol, false)
 }
 
-fn arb_polar_cons(ty: BoxedStrategy<Ty<Constructed, char>>) -> BoxedStrategy<Constructed> {
-    lazy_static! {
-        static ref IDENT: SBoxedStrategy<Rc<str>> =
-            string_regex("[a-z]").unwrap().prop_map(Into::into).sboxed();
-    }
-
-    prop_oneof![
-        LazyJust::new(|| Constructed::Bool),
-        (ty.clone(), ty.clone()).prop_map(|(d, r)| Constructed::Fun(Box::new(d), Box::new(r))),
-        btree_map(IDENT.clone(), ty.prop_map(Box::new), 0..8).prop_map(Constructed::Record)
-    ]
-    .boxed()
+/// Like [`arb_polar_ty`], but also generates variant constructors.
+pub fn arb_variant_ty(pol: Polarity) -> BoxedStrategy<Ty<Constructed, char>> {
+    arb_polar_ty_impl(pol, true)
 }
 
-fn check(
-    pol: Polarity,
-    ty: &Ty<Constructed, char>,
-    recs: &mut VecDeque<Polarity>,
-    unguarded: usize,
-) -> bool {
-    match ty {
-        Ty::BoundVar(idx) => {
-            if *idx < unguarded || *idx >= recs.len() {
-                false
-            } else {
-                recs[*idx] == pol
-            }
-        }
-        Ty::Constructed(Constructed::Fun(d, r)) => {
-            check(-pol, d, recs, 0) && check(pol, r, recs, 0)
-        }
-        Ty::Constructed(Constructed::Record(fields)) => {
-            fields.iter().all(|(_, t)| check(pol, t, recs, 0))
-        }
-        Ty::Add(l, r) => check(pol, l, recs, unguarded) && check(pol, r, recs, unguarded),
-        Ty::Recursive(t) => {
-            recs.push_front(pol);
-            let b = check(pol, t, recs, unguarded + 1);
-            recs.pop_front();
-            b
-        }
-        _ => true,
-    }
+fn arb_polar_ty_impl(pol: Polarity, variants: bool) -> BoxedStrategy<Ty<Constructed, char>> {
+    arbitrary::arb_polar_ty(pol, move |ty| arb_polar_cons(ty, variants))
 }
 
-#[derive(Debug)]
-struct BoundVar;
-struct BoundVarTree(usize);
-
-impl Strategy for BoundVar {
-    type Tree = BoundVarTree;
-    type Value = usize;
-
-    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
-        let val: f64 = runner.rng().sample(Exp1);
-        Ok(BoundVarTree(val as usize))
-    }
+/// A constraint t⁺ ≤ t⁻ between a positive and a negative type.
+pub fn arb_constraint() -> BoxedStrategy<(Ty<Constructed, char>, Ty<Constructed, char>)> {
+    arbitrary::arb_constraint(|ty| arb_polar_cons(ty, false))
 }
 
-impl ValueTree for BoundVarTree {
-    type Value = usize;
-
-    fn current(&self) -> Self::Value {
-        self.0
-    }
+/// Like [`arb_constraint`], but the types may contain variant constructors.
+pub fn arb_variant_constraint() -> BoxedStrategy<(Ty<Constructed, char>, Ty<Constructed, char>)> {
+    arbitrary::arb_constraint(|ty| arb_polar_cons(ty, true))
+}
 
-    fn simplify(&mut self) -> bool {
-        false
+fn arb_polar_cons(
+    ty: BoxedStrategy<Ty<Constructed, char>>,
+    variants: bool,
+) -> BoxedStrategy<Constructed> {
+    lazy_static! {
+        static ref IDENT: SBoxedStrategy<Rc<str>> =
+            string_regex("[a-z]").unwrap().prop_map(Into::into).sboxed();
+        static ref TAG: SBoxedStrategy<Rc<str>> =
+            string_regex("[A-Z]").unwrap().prop_map(Into::into).sboxed();
     }
 
-    fn complicate(&mut self) -> bool {
-        false
+    let cons = prop_oneof![
+        LazyJust::new(|| Constructed::Bool),
+        (ty.clone(), ty.clone()).prop_map(|(d, r)| Constructed::Fun(Box::new(d), Box::new(r))),
+        btree_map(IDENT.clone(), ty.clone().prop_map(Box::new), 0..8).prop_map(Constructed::Record),
+    ];
+    if variants {
+        prop_oneof![
+            3 => cons,
+            1 => btree_map(TAG.clone(), ty.prop_map(Box::new), 0..8).prop_map(Constructed::Variant),
+        ]
+        .boxed()
+    } else {
+        cons.boxed()
     }
 }
 
@@ -132,4 +88,10 @@ proptest! {
 
     #[test]
     fn auto_neg(_ in arb_auto_ty(Polarity::Neg)) {}
+
+    #[test]
+    fn variant_pos(_ in arb_variant_ty(Polarity::Pos)) {}
+
+    #[test]
+    fn variant_neg(_ in arb_variant_ty(Polarity::Neg)) {}
 }
@@ -1,22 +1,28 @@
 use std::collections::BTreeMap;
+use std::fmt;
 use std::rc::Rc;
+use std::str::FromStr;
 
 use super::{Constructor, Label};
 use crate::auto::build::polar::Build;
-use crate::auto::StateSet;
-use crate::polar::Ty;
+use crate::auto::{Decompile, StateSet};
+use crate::polar::{print_fields, Parse, ParseError, Parser, Precedence, Print, Ty};
+use crate::Polarity;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Constructed {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Constructed<V = char> {
     Bool,
-    Fun(Box<Ty<Constructed, char>>, Box<Ty<Constructed, char>>),
-    Record(BTreeMap<Rc<str>, Box<Ty<Constructed, char>>>),
+    Fun(Box<Ty<Constructed<V>, V>>, Box<Ty<Constructed<V>, V>>),
+    Record(BTreeMap<Rc<str>, Box<Ty<Constructed<V>, V>>>),
+    Variant(BTreeMap<Rc<str>, Box<Ty<Constructed<V>, V>>>),
 }
 
-impl Build<Constructor, char> for Constructed {
+impl<V> Build<Constructor, V> for Constructed<V> {
     fn map<'a, F>(&'a self, mut mapper: F) -> Constructor
     where
-        F: FnMut(Label, &'a Ty<Self, char>) -> StateSet,
+        V: 'a,
+        F: FnMut(Label, &'a Ty<Self, V>) -> StateSet,
     {
         match self {
             Constructed::Bool => Constructor::Bool,
@@ -29,6 +35,128 @@ impl Build<Constructor, char> for Constructed {
                     .map(|(label, ty)| (label.clone(), mapper(Label::Label(label.clone()), ty)))
                     .collect(),
             ),
+            Constructed::Variant(tags) => Constructor::Variant(
+                tags.iter()
+                    .map(|(tag, ty)| (tag.clone(), mapper(Label::Tag(tag.clone()), ty)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn visit_params<'a, F>(&'a self, mut visit: F)
+    where
+        V: 'a,
+        F: FnMut(Label, &'a Ty<Self, V>),
+    {
+        match self {
+            Constructed::Bool => (),
+            Constructed::Fun(lhs, rhs) => {
+                visit(Label::Domain, lhs);
+                visit(Label::Range, rhs);
+            }
+            Constructed::Record(fields) => {
+                for (label, ty) in fields {
+                    visit(Label::Label(label.clone()), ty);
+                }
+            }
+            Constructed::Variant(tags) => {
+                for (tag, ty) in tags {
+                    visit(Label::Tag(tag.clone()), ty);
+                }
+            }
+        }
+    }
+}
+
+impl<V> Decompile<Constructor, V> for Constructed<V> {
+    fn decompile<F>(con: &Constructor, mut mapper: F) -> Self
+    where
+        F: FnMut(Label, &StateSet) -> Ty<Self, V>,
+    {
+        match con {
+            Constructor::Bool => Constructed::Bool,
+            Constructor::Fun(d, r) => Constructed::Fun(
+                Box::new(mapper(Label::Domain, d)),
+                Box::new(mapper(Label::Range, r)),
+            ),
+            Constructor::Record(fields) => Constructed::Record(
+                fields
+                    .iter()
+                    .map(|(label, set)| {
+                        (
+                            label.clone(),
+                            Box::new(mapper(Label::Label(label.clone()), set)),
+                        )
+                    })
+                    .collect(),
+            ),
+            Constructor::Variant(tags) => Constructed::Variant(
+                tags.iter()
+                    .map(|(tag, set)| (tag.clone(), Box::new(mapper(Label::Tag(tag.clone()), set))))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<V> Print<V> for Constructed<V> {
+    fn precedence(&self) -> Precedence {
+        match self {
+            Constructed::Fun(..) => Precedence::Operator,
+            Constructed::Bool | Constructed::Record(_) | Constructed::Variant(_) => {
+                Precedence::Atom
+            }
+        }
+    }
+
+    fn print<'a, F>(&'a self, f: &mut fmt::Formatter, mut print: F) -> fmt::Result
+    where
+        V: 'a,
+        F: FnMut(&mut fmt::Formatter, Polarity, Precedence, &'a Ty<Self, V>) -> fmt::Result,
+    {
+        match self {
+            Constructed::Bool => f.write_str("bool"),
+            Constructed::Fun(d, r) => {
+                print(f, Polarity::Neg, Precedence::Atom, d)?;
+                f.write_str(" -> ")?;
+                print(f, Polarity::Pos, Precedence::Operator, r)
+            }
+            Constructed::Record(fields) => {
+                f.write_str("{")?;
+                print_fields(f, fields, &mut print)?;
+                f.write_str("}")
+            }
+            Constructed::Variant(tags) => {
+                f.write_str("[")?;
+                print_fields(f, tags, &mut print)?;
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+impl<V: FromStr> Parse<V> for Constructed<V> {
+    fn parse_atom(parser: &mut Parser<'_, Self, V>) -> Result<Option<Self>, ParseError> {
+        if parser.eat("bool") {
+            Ok(Some(Constructed::Bool))
+        } else if parser.eat("{") {
+            Ok(Some(Constructed::Record(parser.parse_fields("}")?)))
+        } else if parser.eat("[") {
+            Ok(Some(Constructed::Variant(parser.parse_fields("]")?)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_infix(
+        lhs: Ty<Self, V>,
+        parser: &mut Parser<'_, Self, V>,
+    ) -> Result<Result<Self, Ty<Self, V>>, ParseError> {
+        if parser.eat("->") {
+            let rhs = parser.parse(Precedence::Operator)?;
+            Ok(Ok(Constructed::Fun(Box::new(lhs), Box::new(rhs))))
+        } else {
+            Ok(Err(lhs))
         }
     }
 }
@@ -1,7 +1,7 @@
 mod arbitrary;
 mod build;
 
-pub use self::arbitrary::{arb_auto_ty, arb_polar_ty};
+pub use self::arbitrary::{arb_auto_ty, arb_constraint, arb_polar_ty, arb_variant_constraint};
 pub use self::build::Constructed;
 
 use std::cmp::Ordering;
@@ -11,14 +11,17 @@ use std::vec;
 use im::OrdMap;
 use itertools::EitherOrBoth;
 
+use crate::auto::interface::{self, Encode, Reader, Writer};
 use crate::auto::StateSet;
 use crate::Polarity;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Constructor {
     Bool,
     Fun(StateSet, StateSet),
     Record(OrdMap<Rc<str>, StateSet>),
+    Variant(OrdMap<Rc<str>, StateSet>),
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
@@ -26,6 +29,7 @@ pub enum Component {
     Bool,
     Fun,
     Record,
+    Variant,
 }
 
 impl crate::Constructor for Constructor {
@@ -37,6 +41,7 @@ impl crate::Constructor for Constructor {
             Constructor::Bool => Component::Bool,
             Constructor::Fun(..) => Component::Fun,
             Constructor::Record(..) => Component::Record,
+            Constructor::Variant(..) => Component::Variant,
         }
     }
 
@@ -61,6 +66,20 @@ impl crate::Constructor for Constructor {
                     })
                 }
             },
+            (Constructor::Variant(ref mut lhs), Constructor::Variant(ref rhs)) => match pol {
+                Polarity::Pos => {
+                    *lhs = lhs.clone().union_with(rhs.clone(), |mut l, r| {
+                        l.union(&r);
+                        l
+                    })
+                }
+                Polarity::Neg => {
+                    *lhs = lhs.clone().intersection_with(rhs.clone(), |mut l, r| {
+                        l.union(&r);
+                        l
+                    })
+                }
+            },
             _ => unreachable!(),
         }
     }
@@ -91,6 +110,11 @@ impl crate::Constructor for Constructor {
                     .map(|(label, set)| (label.clone(), mapper(Label::Label(label), set)))
                     .collect(),
             ),
+            Constructor::Variant(tags) => Constructor::Variant(
+                tags.into_iter()
+                    .map(|(tag, set)| (tag.clone(), mapper(Label::Tag(tag), set)))
+                    .collect(),
+            ),
         }
     }
 }
@@ -105,6 +129,11 @@ impl Constructor {
                 .into_iter()
                 .map(|(label, set)| (Label::Label(label), set))
                 .collect(),
+            Constructor::Variant(tags) => tags
+                .clone()
+                .into_iter()
+                .map(|(tag, set)| (Label::Tag(tag), set))
+                .collect(),
         }
     }
 }
@@ -117,23 +146,76 @@ impl PartialOrd for Constructor {
             (Constructor::Record(ref lhs), Constructor::Record(ref rhs)) => {
                 iter_set::cmp(lhs.keys(), rhs.keys()).map(Ordering::reverse)
             }
+            (Constructor::Variant(ref lhs), Constructor::Variant(ref rhs)) => {
+                iter_set::cmp(lhs.keys(), rhs.keys())
+            }
             _ => None,
         }
     }
 }
 
+impl Encode for Constructor {
+    fn encode(&self, writer: &mut Writer) {
+        match self {
+            Constructor::Bool => writer.write_u8(0),
+            Constructor::Fun(d, r) => {
+                writer.write_u8(1);
+                writer.write_state_set(d);
+                writer.write_state_set(r);
+            }
+            Constructor::Record(fields) | Constructor::Variant(fields) => {
+                writer.write_u8(match self {
+                    Constructor::Record(_) => 2,
+                    _ => 3,
+                });
+                writer.write_u32(fields.len() as u32);
+                for (label, set) in fields {
+                    writer.write_str(label);
+                    writer.write_state_set(set);
+                }
+            }
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, interface::Error> {
+        let tag = reader.read_u8()?;
+        match tag {
+            0 => Ok(Constructor::Bool),
+            1 => Ok(Constructor::Fun(
+                reader.read_state_set()?,
+                reader.read_state_set()?,
+            )),
+            2 | 3 => {
+                let mut fields = OrdMap::new();
+                for _ in 0..reader.read_u32()? {
+                    let label = Rc::from(reader.read_str()?);
+                    fields.insert(label, reader.read_state_set()?);
+                }
+                Ok(if tag == 2 {
+                    Constructor::Record(fields)
+                } else {
+                    Constructor::Variant(fields)
+                })
+            }
+            _ => Err(interface::Error::Invalid("unknown constructor".to_owned())),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+#[allow(clippy::enum_variant_names)]
 pub enum Label {
     Domain,
     Range,
     Label(Rc<str>),
+    Tag(Rc<str>),
 }
 
 impl crate::Label for Label {
     fn polarity(&self) -> Polarity {
         match self {
             Label::Domain => Polarity::Neg,
-            Label::Range | Label::Label(_) => Polarity::Pos,
+            Label::Range | Label::Label(_) | Label::Tag(_) => Polarity::Pos,
         }
     }
 }